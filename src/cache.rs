@@ -0,0 +1,124 @@
+use std::path::{Path, PathBuf};
+
+use crate::{FormatError, settings::Settings};
+
+const CACHE_NAME: &str = "prettypst";
+const ENTRIES_NAME: &str = "formatted";
+const VERSION: &str = env!("CARGO_PKG_VERSION");
+// 256 shards with at most 64 entries each
+const SHARD_ENTRIES: usize = 64;
+
+// 64-bit FNV-1a
+const FNV_OFFSET: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+/// Files which are known to be formatted, one empty entry per key.
+///
+/// The entries are stored per version of prettypst and sharded by the first two characters of
+/// the key, full shards drop their oldest entry.
+pub struct Cache {
+    entries: PathBuf,
+    folder: PathBuf,
+}
+
+impl Cache {
+    /// Only the entries below `directory` are used and cleared.
+    pub fn new(directory: &Path) -> Self {
+        let entries = directory.join(ENTRIES_NAME);
+        Self {
+            folder: entries.join(VERSION),
+            entries,
+        }
+    }
+
+    /// Folder in the cache directory of the user, `None` if no directory is known.
+    pub fn default_folder() -> Option<PathBuf> {
+        let base = std::env::var_os("XDG_CACHE_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("LOCALAPPDATA").map(PathBuf::from))
+            .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".cache")))?;
+        Some(base.join(CACHE_NAME))
+    }
+
+    /// Key for the content formatted with the settings by this version of prettypst, the same
+    /// for every run and Rust release.
+    pub fn key(content: &str, settings: &Settings) -> String {
+        let settings = serde_json::to_string(settings).expect("settings are serializable");
+        let mut hash = FNV_OFFSET;
+        for part in [VERSION, &settings, content] {
+            for byte in part.bytes().chain(part.len().to_le_bytes()) {
+                hash = (hash ^ u64::from(byte)).wrapping_mul(FNV_PRIME);
+            }
+        }
+        format!("{hash:016x}")
+    }
+
+    pub fn contains(&self, key: &str) -> bool {
+        self.entry(key).is_file()
+    }
+
+    /// Remember the key, failures only cause the file to be formatted again.
+    pub fn insert(&self, key: &str) {
+        if !self.folder.is_dir() {
+            self.remove_other_versions();
+        }
+        let entry = self.entry(key);
+        let Some(shard) = entry.parent() else {
+            return;
+        };
+        if std::fs::create_dir_all(shard).is_ok() {
+            Self::make_room(shard);
+            let _ = std::fs::write(entry, "");
+        }
+    }
+
+    pub fn clear(&self) -> Result<(), FormatError> {
+        match std::fs::remove_dir_all(&self.entries) {
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => {
+                Err(FormatError::FailedToClearCache(err))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn entry(&self, key: &str) -> PathBuf {
+        let (shard, name) = key.split_at(2.min(key.len()));
+        self.folder.join(shard).join(name)
+    }
+
+    /// Entries of older versions are never hit again.
+    fn remove_other_versions(&self) {
+        let Ok(versions) = std::fs::read_dir(&self.entries) else {
+            return;
+        };
+        for version in versions.flatten() {
+            if version.file_name() != VERSION {
+                let _ = std::fs::remove_dir_all(version.path());
+            }
+        }
+    }
+
+    /// Remove the oldest entries until another one fits into the shard.
+    fn make_room(shard: &Path) {
+        let Ok(entries) = std::fs::read_dir(shard) else {
+            return;
+        };
+        let mut entries = entries
+            .flatten()
+            .map(|entry| {
+                let modified = entry
+                    .metadata()
+                    .and_then(|metadata| metadata.modified())
+                    .ok();
+                (modified, entry.path())
+            })
+            .collect::<Vec<_>>();
+        if entries.len() < SHARD_ENTRIES {
+            return;
+        }
+        entries.sort();
+        for (_, path) in &entries[..=entries.len() - SHARD_ENTRIES] {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
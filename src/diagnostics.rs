@@ -0,0 +1,84 @@
+use std::ops::Range;
+
+use serde::Serialize;
+use typst_syntax::{SyntaxKind, SyntaxNode};
+
+/// Error found while parsing, the erroneous region is not formatted.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ParseError {
+    pub message: String,
+    pub hints: Vec<String>,
+    /// Byte range in the source
+    pub range: Range<usize>,
+    /// Line of the start, starting at 1
+    pub line: usize,
+    /// Column of the start in characters, starting at 1
+    pub column: usize,
+}
+
+pub fn parse_errors(root: &SyntaxNode, source: &str) -> Vec<ParseError> {
+    let mut errors = Vec::new();
+    collect(root, 0, source, &mut errors);
+    errors
+}
+
+fn collect(node: &SyntaxNode, offset: usize, source: &str, errors: &mut Vec<ParseError>) {
+    if !node.erroneous() {
+        return;
+    }
+    if node.kind() == SyntaxKind::Error {
+        let before = &source[..offset];
+        let line = before.matches('\n').count() + 1;
+        let column = match before.rfind('\n') {
+            Some(index) => before[index + 1..].chars().count() + 1,
+            None => before.chars().count() + 1,
+        };
+        for error in node.errors() {
+            errors.push(ParseError {
+                message: error.message.to_string(),
+                hints: error.hints.iter().map(|hint| hint.to_string()).collect(),
+                range: offset..offset + node.len(),
+                line,
+                column,
+            });
+        }
+        return;
+    }
+    let mut offset = offset;
+    for child in node.children() {
+        collect(child, offset, source, errors);
+        offset += child.len();
+    }
+}
+
+impl ParseError {
+    /// Message with location and the source line, `name` is the displayed file name.
+    pub fn report(&self, name: &str, source: &str) -> String {
+        let line_start = source[..self.range.start]
+            .rfind('\n')
+            .map_or(0, |index| index + 1);
+        let line_end = source[self.range.start..]
+            .find('\n')
+            .map_or(source.len(), |index| self.range.start + index);
+        let text = source[line_start..line_end].trim_end_matches('\r');
+        let marked = source[self.range.start..self.range.end.min(line_end)]
+            .chars()
+            .count()
+            .max(1);
+
+        let number = self.line.to_string();
+        let padding = " ".repeat(number.len());
+        let mut report = format!(
+            "error: {}\n{padding}--> {name}:{}:{}\n{padding} |\n{number} | {text}\n{padding} | {}{}\n",
+            self.message,
+            self.line,
+            self.column,
+            " ".repeat(self.column - 1),
+            "^".repeat(marked),
+        );
+        for hint in self.hints.iter() {
+            report.push_str(&format!("{padding} = hint: {hint}\n"));
+        }
+        report
+    }
+}
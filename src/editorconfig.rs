@@ -0,0 +1,309 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    FormatError,
+    settings::{LineEnding, Settings},
+};
+
+const EDITORCONFIG_NAME: &str = ".editorconfig";
+
+struct Section {
+    pattern: Vec<Token>,
+    properties: Vec<(String, String)>,
+}
+
+struct EditorConfig {
+    folder: PathBuf,
+    root: bool,
+    sections: Vec<Section>,
+}
+
+/// Apply the `.editorconfig` properties for `document` to the settings.
+///
+/// The document does not need to exist, only its location is used.
+pub fn apply(settings: &mut Settings, document: &Path) -> Result<(), FormatError> {
+    let document =
+        std::path::absolute(document).map_err(FormatError::FailedToGetWorkingDirectory)?;
+
+    let mut configs = Vec::new();
+    let mut folder = document.parent();
+    while let Some(path) = folder {
+        let file = path.join(EDITORCONFIG_NAME);
+        if file.is_file() {
+            let data =
+                std::fs::read_to_string(&file).map_err(FormatError::FailedToReadEditorConfig)?;
+            let config = EditorConfig::parse(&data, path.to_owned());
+            let root = config.root;
+            configs.push(config);
+            if root {
+                break;
+            }
+        }
+        folder = path.parent();
+    }
+
+    // closer files take precedence
+    let mut properties = HashMap::new();
+    for config in configs.iter().rev() {
+        let Ok(relative) = document.strip_prefix(&config.folder) else {
+            continue;
+        };
+        let relative = format!("/{}", relative.display()).replace('\\', "/");
+        let relative = relative.chars().collect::<Vec<_>>();
+        for section in config.sections.iter() {
+            if matches(&section.pattern, &relative) {
+                properties.extend(section.properties.iter().cloned());
+            }
+        }
+    }
+
+    overwrite(settings, &properties);
+    Ok(())
+}
+
+fn overwrite(settings: &mut Settings, properties: &HashMap<String, String>) {
+    let get = |key: &str| properties.get(key).map(String::as_str);
+    let size = |value: &str| value.parse::<usize>().ok().filter(|&size| size > 0);
+
+    let tab_width = get("tab_width").and_then(size);
+    let indent_size = match get("indent_size") {
+        Some("tab") => tab_width,
+        Some(value) => size(value),
+        None => None,
+    };
+    match get("indent_style") {
+        Some("tab") => settings.indentation = 0,
+        Some("space") => {
+            settings.indentation = indent_size.unwrap_or(match settings.indentation {
+                0 => 2,
+                amount => amount,
+            })
+        }
+        _ => {
+            if let Some(amount) = indent_size
+                && settings.indentation != 0
+            {
+                settings.indentation = amount;
+            }
+        }
+    }
+
+    match get("max_line_length") {
+        Some("off") => settings.automatic_newline.max_width = 0,
+        Some(value) => {
+            if let Some(width) = size(value) {
+                settings.automatic_newline.max_width = width;
+            }
+        }
+        None => {}
+    }
+
+    match get("end_of_line") {
+        Some("lf") => settings.line_ending = LineEnding::Lf,
+        Some("crlf") => settings.line_ending = LineEnding::Crlf,
+        _ => {}
+    }
+
+    match get("insert_final_newline") {
+        Some("true") => settings.final_newline = true,
+        Some("false") => settings.final_newline = false,
+        _ => {}
+    }
+}
+
+impl EditorConfig {
+    fn parse(data: &str, folder: PathBuf) -> Self {
+        let mut root = false;
+        let mut sections = Vec::<Section>::new();
+        for line in data.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+                continue;
+            }
+            if let Some(name) = line
+                .strip_prefix('[')
+                .and_then(|line| line.strip_suffix(']'))
+            {
+                // names without '/' match in any subfolder
+                let pattern = match name.strip_prefix('/') {
+                    Some(_) => name.to_owned(),
+                    None if name.contains('/') => format!("/{name}"),
+                    None => format!("**/{name}"),
+                };
+                sections.push(Section {
+                    pattern: parse(&pattern.chars().collect::<Vec<_>>(), &mut 0, false),
+                    properties: Vec::new(),
+                });
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            let key = key.trim().to_lowercase();
+            let value = value.trim().to_lowercase();
+            match sections.last_mut() {
+                Some(section) => section.properties.push((key, value)),
+                None if key == "root" => root = value == "true",
+                None => {}
+            }
+        }
+        Self {
+            folder,
+            root,
+            sections,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Token {
+    Literal(char),
+    /// `?`
+    Any,
+    /// `*`
+    Star,
+    /// `**`
+    DoubleStar,
+    /// `[...]` or `[!...]`
+    Class {
+        negated: bool,
+        ranges: Vec<(char, char)>,
+    },
+    /// `{1..5}`
+    Range(i64, i64),
+    /// `{a,b,...}`
+    Alternatives(Vec<Vec<Token>>),
+}
+
+fn parse(pattern: &[char], index: &mut usize, nested: bool) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    while let Some(&symbol) = pattern.get(*index) {
+        if nested && (symbol == ',' || symbol == '}') {
+            break;
+        }
+        *index += 1;
+        match symbol {
+            '\\' => match pattern.get(*index) {
+                Some(&escaped) => {
+                    *index += 1;
+                    tokens.push(Token::Literal(escaped));
+                }
+                None => tokens.push(Token::Literal('\\')),
+            },
+            '?' => tokens.push(Token::Any),
+            '*' if pattern.get(*index) == Some(&'*') => {
+                *index += 1;
+                tokens.push(Token::DoubleStar);
+            }
+            '*' => tokens.push(Token::Star),
+            '[' => tokens.push(parse_class(pattern, index).unwrap_or(Token::Literal('['))),
+            '{' => tokens.push(parse_braces(pattern, index).unwrap_or(Token::Literal('{'))),
+            symbol => tokens.push(Token::Literal(symbol)),
+        }
+    }
+    tokens
+}
+
+fn parse_class(pattern: &[char], index: &mut usize) -> Option<Token> {
+    let mut current = *index;
+    let negated = matches!(pattern.get(current), Some('!' | '^'));
+    if negated {
+        current += 1;
+    }
+    let mut ranges = Vec::new();
+    loop {
+        match *pattern.get(current)? {
+            ']' => break,
+            start
+                if pattern.get(current + 1) == Some(&'-')
+                    && pattern.get(current + 2).is_some_and(|&end| end != ']') =>
+            {
+                ranges.push((start, pattern[current + 2]));
+                current += 3;
+            }
+            symbol => {
+                ranges.push((symbol, symbol));
+                current += 1;
+            }
+        }
+    }
+    *index = current + 1;
+    Some(Token::Class { negated, ranges })
+}
+
+fn parse_braces(pattern: &[char], index: &mut usize) -> Option<Token> {
+    let start = *index;
+    if let Some(length) = pattern[start..].iter().position(|&symbol| symbol == '}') {
+        let content = pattern[start..start + length].iter().collect::<String>();
+        if let Some((low, high)) = content.split_once("..")
+            && let (Ok(low), Ok(high)) = (low.parse(), high.parse())
+        {
+            *index = start + length + 1;
+            return Some(Token::Range(low, high));
+        }
+    }
+
+    let mut alternatives = Vec::new();
+    loop {
+        alternatives.push(parse(pattern, index, true));
+        match pattern.get(*index) {
+            Some(',') => *index += 1,
+            Some('}') => {
+                *index += 1;
+                break;
+            }
+            _ => {
+                *index = start;
+                return None;
+            }
+        }
+    }
+    // `{single}` is taken literally
+    if alternatives.len() < 2 {
+        *index = start;
+        return None;
+    }
+    Some(Token::Alternatives(alternatives))
+}
+
+fn matches(tokens: &[Token], text: &[char]) -> bool {
+    let Some((token, rest)) = tokens.split_first() else {
+        return text.is_empty();
+    };
+    match token {
+        Token::Literal(symbol) => text.first() == Some(symbol) && matches(rest, &text[1..]),
+        Token::Any => text.first().is_some_and(|&c| c != '/') && matches(rest, &text[1..]),
+        Token::Star => (0..=text.len())
+            .take_while(|&length| length == 0 || text[length - 1] != '/')
+            .any(|length| matches(rest, &text[length..])),
+        Token::DoubleStar => (0..=text.len()).any(|length| matches(rest, &text[length..])),
+        Token::Class { negated, ranges } => {
+            text.first().is_some_and(|&c| {
+                c != '/' && ranges.iter().any(|&(low, high)| low <= c && c <= high) != *negated
+            }) && matches(rest, &text[1..])
+        }
+        Token::Range(low, high) => {
+            let sign = usize::from(text.first() == Some(&'-'));
+            let digits = text[sign..]
+                .iter()
+                .take_while(|c| c.is_ascii_digit())
+                .count();
+            (1..=digits).any(|length| {
+                let length = sign + length;
+                text[..length]
+                    .iter()
+                    .collect::<String>()
+                    .parse::<i64>()
+                    .is_ok_and(|value| *low <= value && value <= *high)
+                    && matches(rest, &text[length..])
+            })
+        }
+        Token::Alternatives(alternatives) => alternatives.iter().any(|alternative| {
+            let tokens = alternative.iter().chain(rest).cloned().collect::<Vec<_>>();
+            matches(&tokens, text)
+        }),
+    }
+}
@@ -0,0 +1,171 @@
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+use crate::{FormatError, report::LineRange};
+
+const PATHSPEC: &str = "*.typ";
+
+/// Typst files changed since `revision` or staged in the index, deleted files are excluded.
+pub fn changed_files(revision: Option<&str>, staged: bool) -> Result<Vec<PathBuf>, FormatError> {
+    let root = root(None)?;
+
+    let mut arguments = vec!["diff", "--name-only", "-z", "--diff-filter=d"];
+    if staged {
+        arguments.push("--cached");
+    }
+    arguments.extend(revision);
+    arguments.extend(["--", PATHSPEC]);
+
+    // pathspecs are relative to the working directory
+    let names = run(&arguments, Some(&root))?;
+    Ok(names
+        .split('\0')
+        .filter(|name| !name.is_empty())
+        .map(|name| root.join(name))
+        .collect())
+}
+
+/// Lines of `path` changed since `revision`, in the index if `staged` or else in the working tree.
+pub fn changed_lines(
+    path: &Path,
+    revision: Option<&str>,
+    staged: bool,
+) -> Result<Vec<LineRange>, FormatError> {
+    let path = path_argument(path);
+    let mut arguments = vec!["diff", "--unified=0", "--no-color", "--no-ext-diff"];
+    if staged {
+        arguments.push("--cached");
+    }
+    arguments.extend(revision);
+    arguments.extend(["--", &path]);
+
+    // '@@ -<old> +<start>[,<count>] @@', removed lines mark the line in front of them
+    let diff = run(&arguments, None)?;
+    let lines = diff
+        .lines()
+        .filter_map(|line| {
+            line.strip_prefix("@@ -")?
+                .split(' ')
+                .nth(1)?
+                .strip_prefix('+')
+        })
+        .filter_map(|hunk| {
+            let (start, count) = hunk.split_once(',').unwrap_or((hunk, "1"));
+            let (start, count) = (start.parse::<usize>().ok()?, count.parse::<usize>().ok()?);
+            Some(LineRange {
+                start: start.max(1),
+                end: (start + count).saturating_sub(1).max(start).max(1),
+            })
+        })
+        .collect();
+    Ok(lines)
+}
+
+/// Check if the working tree version of `path` differs from the index.
+pub fn has_unstaged_changes(path: &Path) -> Result<bool, FormatError> {
+    let path = path_argument(path);
+    let names = run(&["diff", "--name-only", "--", &path], None)?;
+    Ok(!names.trim().is_empty())
+}
+
+/// Content of `path` in the index.
+pub fn staged_content(path: &Path) -> Result<String, FormatError> {
+    let (directory, name) = split(path);
+    run(&["show", &format!(":./{name}")], Some(directory))
+}
+
+/// Add the working tree version of `path` to the index.
+pub fn stage(path: &Path) -> Result<(), FormatError> {
+    run(&["add", "--", &path_argument(path)], None)?;
+    Ok(())
+}
+
+/// Replace the content of `path` in the index without changing the working tree.
+pub fn stage_content(path: &Path, content: &str) -> Result<(), FormatError> {
+    let (directory, name) = split(path);
+    let entry = run(
+        &["ls-files", "--stage", "--full-name", "-z", "--", name],
+        Some(directory),
+    )?;
+    // '<mode> <object> <stage>\t<path>', the index uses paths from the root
+    let (mode, full_name) = entry
+        .split_once('\t')
+        .and_then(|(info, full_name)| {
+            Some((info.split(' ').next()?, full_name.trim_end_matches('\0')))
+        })
+        .ok_or_else(|| FormatError::GitFailed(format!("'{}' is not staged", path.display())))?;
+    let root = root(Some(directory))?;
+
+    let mut child = Command::new("git")
+        .args(["hash-object", "-w", "--stdin", "--path", name])
+        .current_dir(directory)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(FormatError::FailedToRunGit)?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(content.as_bytes())
+            .map_err(FormatError::FailedToRunGit)?;
+    }
+    let hash = output(
+        child
+            .wait_with_output()
+            .map_err(FormatError::FailedToRunGit)?,
+    )?;
+
+    let info = format!("{},{},{}", mode, hash.trim(), full_name);
+    run(&["update-index", "--cacheinfo", &info], Some(&root))?;
+    Ok(())
+}
+
+/// Folder for the hooks of the repository, `core.hooksPath` is respected.
+pub fn hooks_folder() -> Result<PathBuf, FormatError> {
+    let root = root(None)?;
+    if let Ok(configured) = run(&["config", "--get", "core.hooksPath"], None)
+        && !configured.trim().is_empty()
+    {
+        return Ok(root.join(configured.trim_end()));
+    }
+    let hooks = run(&["rev-parse", "--git-path", "hooks"], None)?;
+    std::path::absolute(hooks.trim_end()).map_err(FormatError::FailedToGetWorkingDirectory)
+}
+
+fn root(directory: Option<&Path>) -> Result<PathBuf, FormatError> {
+    let root = run(&["rev-parse", "--show-toplevel"], directory)?;
+    Ok(PathBuf::from(root.trim_end()))
+}
+
+fn split(path: &Path) -> (&Path, &str) {
+    let directory = path.parent().unwrap_or(Path::new("."));
+    let name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or_default();
+    (directory, name)
+}
+
+fn path_argument(path: &Path) -> String {
+    path.display().to_string()
+}
+
+fn run(arguments: &[&str], directory: Option<&Path>) -> Result<String, FormatError> {
+    let mut command = Command::new("git");
+    command.args(arguments);
+    if let Some(directory) = directory {
+        command.current_dir(directory);
+    }
+    output(command.output().map_err(FormatError::FailedToRunGit)?)
+}
+
+fn output(output: std::process::Output) -> Result<String, FormatError> {
+    if !output.status.success() {
+        let message = String::from_utf8_lossy(&output.stderr);
+        return Err(FormatError::GitFailed(message.trim().into()));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
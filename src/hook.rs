@@ -0,0 +1,48 @@
+use std::path::PathBuf;
+
+use crate::{FormatError, git};
+
+/// Marks hooks which can be replaced without `--force`.
+const MARKER: &str = "# Installed by 'prettypst install-hook'";
+
+/// Write a git pre-commit hook which checks the staged Typst files, `arguments` are added to
+/// every call of prettypst.
+pub fn install(arguments: &[String], force: bool) -> Result<PathBuf, FormatError> {
+    let hooks = git::hooks_folder()?;
+    let path = hooks.join("pre-commit");
+    if !force
+        && let Ok(existing) = std::fs::read_to_string(&path)
+        && !existing.contains(MARKER)
+    {
+        return Err(FormatError::HookAlreadyExists(path));
+    }
+
+    std::fs::create_dir_all(&hooks).map_err(FormatError::FailedToInstallHook)?;
+    std::fs::write(&path, script(arguments)).map_err(FormatError::FailedToInstallHook)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755))
+            .map_err(FormatError::FailedToInstallHook)?;
+    }
+    Ok(path)
+}
+
+fn script(arguments: &[String]) -> String {
+    let arguments = arguments
+        .iter()
+        .map(|argument| format!(" '{}'", argument.replace('\'', r"'\''")))
+        .collect::<String>();
+    format!(
+        r#"#!/bin/sh
+{MARKER}, checks the staged Typst files.
+if ! prettypst --staged --check{arguments}; then
+    echo >&2
+    echo "prettypst: the staged Typst files above are not formatted." >&2
+    echo "Run 'prettypst --staged{arguments}' to format and stage them, then commit again." >&2
+    echo "Use 'git commit --no-verify' to commit without the check." >&2
+    exit 1
+fi
+"#
+    )
+}
@@ -1,57 +1,252 @@
+mod cache;
+mod diagnostics;
+mod editorconfig;
+mod git;
+mod hook;
 mod logic;
+mod lsp;
 mod output;
+mod report;
+mod schema;
+mod selection;
 mod settings;
 mod state;
 mod styles;
+mod watch;
 
 use std::{
+    borrow::Cow,
     fs::{self, File},
-    io::{BufWriter, Read},
-    path::PathBuf,
+    io::{ErrorKind, Read, Write},
+    path::{Path, PathBuf},
+    time::Instant,
 };
 
-use clap::Parser;
+use cache::Cache;
+use clap::{ArgGroup, Parser, Subcommand};
 use output::Output;
+use selection::Selection;
+use similar::TextDiff;
 use state::State;
 use typst_syntax::{SyntaxKind, SyntaxNode};
 
-pub use crate::{output::OutputTarget, settings::Settings, styles::Styles};
+pub use crate::{
+    diagnostics::ParseError,
+    output::OutputTarget,
+    report::{FileReport, LineRange, REPORT_VERSION, ReportFormat},
+    schema::settings_schema,
+    settings::Settings,
+    styles::Styles,
+};
 
 const CONFIG_NAME: &str = "prettypst.toml";
+const STDIN_NAME: &str = "stdin";
+const STDIN_DOCUMENT: &str = "stdin.typ";
 
 #[derive(Debug, Clone, Parser)]
+#[command(group(ArgGroup::new("git").args(["changed_since", "staged"]).multiple(true)))]
 pub struct Command {
+    #[command(subcommand)]
+    pub action: Option<Action>,
+
     /// Input path for source file, used as output path if nothing else is specified
     #[arg(default_value = None)]
     pub path: Option<PathBuf>,
 
+    /// Additional input files, each formatted in place like the first
+    #[arg(conflicts_with_all = ["use_std_in", "output", "use_std_out"])]
+    pub paths: Vec<PathBuf>,
+
     /// Output path
-    #[arg(short, long, default_value = None)]
+    #[arg(global = true, short, long, default_value = None)]
     pub output: Option<PathBuf>,
 
     /// Base style for the formatting settings
-    #[arg(short, long, default_value_t = Styles::Default)]
+    #[arg(global = true, short, long, default_value_t = Styles::Default)]
     pub style: Styles,
 
     /// Search for 'prettypst.toml' for additional formatting settings
-    #[arg(long, default_value_t = false)]
+    #[arg(global = true, long, default_value_t = false)]
     pub use_configuration: bool,
 
+    /// Use '.editorconfig' for formatting settings, overwritten by 'prettypst.toml'
+    #[arg(global = true, long, default_value_t = false)]
+    pub use_editorconfig: bool,
+
+    /// Overwrite a formatting setting, applied after 'prettypst.toml'
+    #[arg(global = true, long = "set", value_name = "KEY=VALUE")]
+    pub settings: Vec<String>,
+
     /// Generate file with formatting settings based on the style
-    #[arg(long, default_value_t = false)]
+    #[arg(global = true, long, default_value_t = false)]
     pub save_configuration: bool,
 
+    /// Only check if the input is formatted, without writing any output
+    #[arg(global = true, long, default_value_t = false)]
+    pub check: bool,
+
+    /// Print the changes as a unified diff, without writing any output
+    #[arg(global = true, long, default_value_t = false, conflicts_with_all = ["check", "report", "output", "use_std_out"])]
+    pub diff: bool,
+
+    /// Fail without writing the output if the source has parse errors
+    #[arg(global = true, long, default_value_t = false)]
+    pub fail_on_parse_error: bool,
+
+    /// Print a report for every file to standard output instead of messages
+    #[arg(global = true, long, value_enum, default_value = None)]
+    pub report: Option<ReportFormat>,
+
+    /// Format the Typst files changed since the git revision instead of a single input
+    #[arg(long, value_name = "REV", conflicts_with_all = ["path", "paths", "use_std_in", "output", "use_std_out"])]
+    pub changed_since: Option<String>,
+
+    /// Format the staged Typst files and stage the result
+    #[arg(long, default_value_t = false, conflicts_with_all = ["path", "paths", "use_std_in", "output", "use_std_out"])]
+    pub staged: bool,
+
+    /// Watch the Typst files in the paths and format them when they change
+    #[arg(long, value_name = "PATH", num_args = 1.., conflicts_with_all = ["path", "paths", "use_std_in", "output", "use_std_out", "check", "git"])]
+    pub watch: Option<Vec<PathBuf>>,
+
+    /// Only format the lines changed in git, used with '--changed-since' or '--staged'
+    #[arg(
+        long,
+        default_value_t = false,
+        requires = "git",
+        conflicts_with = "lines"
+    )]
+    pub only_changed_lines: bool,
+
+    /// Only format the paragraphs and blocks around the lines, e.g. '10-40,80-90'
+    #[arg(global = true, long, value_delimiter = ',', value_parser = parse_line_range)]
+    pub lines: Option<Vec<LineRange>>,
+
+    /// Format files even if the cache marks them as formatted
+    #[arg(global = true, long, default_value_t = false)]
+    pub no_cache: bool,
+
+    /// Directory for the cache, defaults to the cache directory of the user
+    #[arg(global = true, long, env = "PRETTYPST_CACHE_DIR", default_value = None)]
+    pub cache_dir: Option<PathBuf>,
+
+    /// Remove all cache entries, nothing else is done without an input
+    #[arg(global = true, long, default_value_t = false)]
+    pub clear_cache: bool,
+
+    /// Print a JSON Schema for 'prettypst.toml'
+    #[arg(global = true, long, default_value_t = false)]
+    pub print_schema: bool,
+
     /// Use standard input as source
-    #[arg(long, default_value_t = false)]
+    #[arg(global = true, long, default_value_t = false)]
     pub use_std_in: bool,
 
     /// Use standard output as target
-    #[arg(long, default_value_t = false)]
+    #[arg(global = true, long, default_value_t = false)]
     pub use_std_out: bool,
 
     /// File location to search for configuration, defaults to input path if available
-    #[arg(long, default_value = None)]
+    #[arg(global = true, long, default_value = None)]
     pub file_location: Option<PathBuf>,
+
+    /// Path of the document read from standard input, used for configuration and messages
+    #[arg(global = true, long, default_value = None, requires = "use_std_in")]
+    pub stdin_filepath: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Subcommand)]
+pub enum Action {
+    /// Format the input, the default without a command
+    Format {
+        /// Input paths for source files, used as output paths if nothing else is specified
+        paths: Vec<PathBuf>,
+    },
+    /// Only check if the input is formatted, like '--check'
+    Check {
+        /// Input paths for source files
+        paths: Vec<PathBuf>,
+    },
+    /// Print the changes as a unified diff without writing them, like '--diff'
+    Diff {
+        /// Input paths for source files
+        paths: Vec<PathBuf>,
+    },
+    /// Create, show or locate the configuration
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+    /// Run a language server which formats documents, using standard input and output
+    Lsp,
+    /// Show the type and the values of a setting, e.g. 'heading.blank-lines-before'
+    Explain {
+        /// Name of the setting, every setting is listed if missing
+        setting: Option<String>,
+    },
+    /// Install a git pre-commit hook which checks the staged Typst files
+    InstallHook {
+        /// Replace an existing pre-commit hook
+        #[arg(long, default_value_t = false)]
+        force: bool,
+
+        /// Additional arguments for prettypst in the hook, e.g. '-- --use-configuration'
+        #[arg(last = true)]
+        arguments: Vec<String>,
+    },
+}
+
+#[derive(Debug, Clone, Subcommand)]
+pub enum ConfigAction {
+    /// Save the settings of the style to 'prettypst.toml', like '--save-configuration'
+    Init,
+    /// Print the settings used for the document
+    Show {
+        /// Document to format, defaults to the input path
+        path: Option<PathBuf>,
+    },
+    /// Print the path of the 'prettypst.toml' used for the document
+    Path {
+        /// Document to format, defaults to the input path
+        path: Option<PathBuf>,
+    },
+    /// Print a JSON Schema for 'prettypst.toml', like '--print-schema'
+    Schema,
+}
+
+impl Command {
+    /// Apply the subcommand to the options, which stay available as aliases.
+    fn resolve_action(&self) -> Self {
+        let mut command = self.clone();
+        match command.action.take() {
+            Some(Action::Format { paths }) => command.replace_paths(paths),
+            Some(Action::Check { paths }) => {
+                command.replace_paths(paths);
+                command.check = true;
+            }
+            Some(Action::Diff { paths }) => {
+                command.replace_paths(paths);
+                command.diff = true;
+            }
+            Some(Action::Config {
+                action: ConfigAction::Init,
+            }) => command.save_configuration = true,
+            Some(Action::Config {
+                action: ConfigAction::Schema,
+            }) => command.print_schema = true,
+            action => command.action = action,
+        }
+        command
+    }
+
+    /// Inputs of the subcommand, which replace the inputs given before it.
+    fn replace_paths(&mut self, paths: Vec<PathBuf>) {
+        let mut paths = paths.into_iter();
+        if let Some(path) = paths.next() {
+            self.path = Some(path);
+            self.paths = paths.collect();
+        }
+    }
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -60,16 +255,27 @@ pub enum FormatError {
     FailedToGetProjectFolder,
     #[error("Failed to get working directory")]
     FailedToGetWorkingDirectory(std::io::Error),
+    #[error("failed to read '.editorconfig'")]
+    FailedToReadEditorConfig(std::io::Error),
     #[error("No configuration file")]
     NoConfigurationFile,
     #[error("Failed to read configuration file")]
     FailedToReadConfigurationFile(std::io::Error),
     #[error("malformed configuration file: {0}")]
     MalformatedConfigurationFile(#[from] toml::de::Error),
+    #[error("missing value for setting '{0}', use 'key=value'")]
+    MissingSettingValue(String),
+    #[error("invalid setting: {0}")]
+    InvalidSettingValue(toml::de::Error),
     #[error("failed to serialize configuration: {0}")]
     FailedToSerializeConfiguration(#[from] toml::ser::Error),
     #[error("failed to save configuration file")]
     FailedToSaveConfigurationFile(std::io::Error),
+    #[error("failed to serialize schema: {0}")]
+    FailedToSerializeSchema(#[from] serde_json::Error),
+
+    #[error("unknown setting '{0}'")]
+    UnknownSetting(String),
 
     #[error("failed to read from stdin")]
     FailedToReadStdIn(std::io::Error),
@@ -80,8 +286,17 @@ pub enum FormatError {
     #[error("failed to read input file")]
     FailedToReadInputFile(std::io::Error),
 
+    #[error("found {0} parse error(s)")]
+    ParseErrors(usize),
+    #[error("'{0}' is not formatted")]
+    NotFormatted(String),
+
     #[error("output file and stdout specified")]
     OutputFileAndStdOutSpecified,
+    #[error("report and stdout specified")]
+    ReportAndStdOutSpecified,
+    #[error("failed to write to stdout")]
+    FailedToWriteStdOut(std::io::Error),
     #[error("failed to create output file")]
     FailedToCreateOutputFile(std::io::Error),
     #[error("failed to create temporary file")]
@@ -90,10 +305,109 @@ pub enum FormatError {
     FailedToGetTemporaryFilePath(std::io::Error),
     #[error("failed to replace input file")]
     FailedToReplaceInputFile(std::io::Error),
+    #[error("failed to clear cache")]
+    FailedToClearCache(std::io::Error),
+    #[error("failed to watch files: {0}")]
+    FailedToWatch(notify::Error),
+
+    #[error("failed to run git")]
+    FailedToRunGit(std::io::Error),
+    #[error("git failed: {0}")]
+    GitFailed(String),
+
+    #[error("'{0}' already exists, use '--force' to replace it")]
+    HookAlreadyExists(PathBuf),
+    #[error("failed to install hook")]
+    FailedToInstallHook(std::io::Error),
+
+    #[error("{}", display_errors(.0))]
+    Files(Vec<FormatError>),
+}
+
+fn display_errors(errors: &[FormatError]) -> String {
+    let messages = errors.iter().map(|err| err.to_string()).collect::<Vec<_>>();
+    messages.join("\n")
+}
+
+/// Exit code of the command line tool.
+///
+/// With several files the most severe code of the files is used, which is the highest one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(i32)]
+pub enum ExitCode {
+    /// Formatted or nothing to change
+    Success = 0,
+    /// Input is not formatted, only with `--check` or `--diff`
+    ChangesNeeded = 1,
+    /// Invalid combination of arguments, also used for invalid arguments, unknown setting names
+    /// and existing hooks without `--force`
+    Usage = 2,
+    /// Input has parse errors, only with `--fail-on-parse-error`
+    ParseError = 3,
+    /// Configuration, '.editorconfig' or `--set` values could not be found, read or parsed
+    ConfigurationError = 4,
+    /// Reading the input, writing the output, the cache, watching or running git failed
+    IoError = 5,
+    /// Settings or the schema could not be serialized, which is a bug in prettypst
+    Internal = 6,
+}
+
+impl FormatError {
+    pub fn exit_code(&self) -> ExitCode {
+        match self {
+            Self::NotFormatted(_) => ExitCode::ChangesNeeded,
+
+            Self::NoInputFileOrStdInSpecified
+            | Self::InputFileAndStdInSpecified
+            | Self::OutputFileAndStdOutSpecified
+            | Self::ReportAndStdOutSpecified
+            | Self::HookAlreadyExists(_)
+            | Self::UnknownSetting(_) => ExitCode::Usage,
+
+            Self::ParseErrors(_) => ExitCode::ParseError,
+
+            Self::FailedToGetProjectFolder
+            | Self::FailedToReadEditorConfig(_)
+            | Self::NoConfigurationFile
+            | Self::FailedToReadConfigurationFile(_)
+            | Self::MalformatedConfigurationFile(_)
+            | Self::MissingSettingValue(_)
+            | Self::InvalidSettingValue(_) => ExitCode::ConfigurationError,
+
+            Self::FailedToGetWorkingDirectory(_)
+            | Self::FailedToSaveConfigurationFile(_)
+            | Self::FailedToReadStdIn(_)
+            | Self::FailedToReadInputFile(_)
+            | Self::FailedToCreateOutputFile(_)
+            | Self::FailedToCreateTemporaryFile(_)
+            | Self::FailedToGetTemporaryFilePath(_)
+            | Self::FailedToReplaceInputFile(_)
+            | Self::FailedToClearCache(_)
+            | Self::FailedToWatch(_)
+            | Self::FailedToRunGit(_)
+            | Self::GitFailed(_)
+            | Self::FailedToInstallHook(_)
+            | Self::FailedToWriteStdOut(_) => ExitCode::IoError,
+
+            Self::FailedToSerializeConfiguration(_) | Self::FailedToSerializeSchema(_) => {
+                ExitCode::Internal
+            }
+
+            // the most severe code of the files
+            Self::Files(errors) => errors
+                .iter()
+                .map(Self::exit_code)
+                .max_by_key(|code| *code as i32)
+                .unwrap_or(ExitCode::Success),
+        }
+    }
 }
 
 pub fn format_node(node: &SyntaxNode, settings: &Settings, target: &mut impl OutputTarget) {
-    let mut output = Output::new(target);
+    format_output(node, settings, Output::new(target));
+}
+
+fn format_output(node: &SyntaxNode, settings: &Settings, mut output: Output<impl OutputTarget>) {
     let state = State::new(settings);
     logic::format(node, state, settings, &mut output);
 
@@ -110,47 +424,122 @@ pub fn format_node(node: &SyntaxNode, settings: &Settings, target: &mut impl Out
     output.finish(&state, settings);
 }
 
-pub fn format_str(text: &str, settings: &settings::Settings, target: &mut impl OutputTarget) {
-    format_node(&typst_syntax::parse(text), settings, target)
+/// Format `text` and return the parse errors, erroneous regions are not formatted.
+pub fn format_str(
+    text: &str,
+    settings: &settings::Settings,
+    target: &mut impl OutputTarget,
+) -> Vec<ParseError> {
+    let root = typst_syntax::parse(text);
+    format_node(&root, settings, target);
+    diagnostics::parse_errors(&root, text)
 }
 
-pub fn format(command: &Command) -> Result<(), FormatError> {
-    let mut settings = command.style.settings();
+/// Format only the paragraphs and blocks around `lines` and keep the rest of `text`.
+pub fn format_lines(
+    text: &str,
+    settings: &Settings,
+    lines: &[LineRange],
+) -> (String, Vec<ParseError>) {
+    let root = typst_syntax::parse(text);
+    let errors = diagnostics::parse_errors(&root, text);
+    let mut selection = Selection::new(&root, text, lines);
+    let mut output = Vec::new();
+    format_output(
+        &root,
+        settings,
+        Output::new(&mut output).with_selection(&mut selection),
+    );
+    let output = String::from_utf8_lossy(&output);
+    (selection.apply(text, &output), errors)
+}
 
-    if command.use_configuration {
-        let path = match (&command.file_location, &command.path) {
-            (Some(path), _) => {
-                if path.extension().is_some() {
-                    path.parent()
-                        .ok_or(FormatError::FailedToGetProjectFolder)?
-                        .to_owned()
-                } else {
-                    path.to_owned()
+pub fn format(command: &Command) -> Result<(), FormatError> {
+    let command = &command.resolve_action();
+    match &command.action {
+        Some(Action::InstallHook { force, arguments }) => {
+            let path = hook::install(arguments, *force)?;
+            eprintln!("installed '{}'", path.display());
+            return Ok(());
+        }
+        Some(Action::Config {
+            action: ConfigAction::Show { path },
+        }) => {
+            let document = match path {
+                Some(path) => {
+                    std::path::absolute(path).map_err(FormatError::FailedToGetWorkingDirectory)?
                 }
-            }
-            (_, Some(path)) => path.to_owned(),
-            _ => std::env::current_dir()
-                .map_err(FormatError::FailedToGetWorkingDirectory)?
-                .to_owned(),
-        };
-        let mut path = path.as_path();
-        let file = loop {
-            let mut file = PathBuf::from(path);
-            file.push(CONFIG_NAME);
-            if file.is_file() {
-                break file;
-            }
-            path = path.parent().ok_or(FormatError::NoConfigurationFile)?;
-        };
-        settings.overwrite(&file)?;
+                None => document_path(command)?,
+            };
+            let (settings, _) = resolve_settings(command, &document)?;
+            print!("{}", toml::to_string_pretty(&settings)?);
+            return Ok(());
+        }
+        Some(Action::Config {
+            action: ConfigAction::Path { path },
+        }) => {
+            let document = match path {
+                Some(path) => {
+                    std::path::absolute(path).map_err(FormatError::FailedToGetWorkingDirectory)?
+                }
+                None => document_path(command)?,
+            };
+            println!("{}", find_configuration(&document)?.display());
+            return Ok(());
+        }
+        Some(Action::Lsp) => return lsp::run(command),
+        Some(Action::Explain { setting }) => {
+            print!("{}", schema::explain(setting.as_deref())?);
+            return Ok(());
+        }
+        _ => {}
+    }
+
+    if command.print_schema {
+        println!("{}", serde_json::to_string_pretty(&settings_schema())?);
+        return Ok(());
     }
 
+    let cache_dir = command.cache_dir.clone().or_else(Cache::default_folder);
+    if command.clear_cache {
+        if let Some(directory) = &cache_dir {
+            Cache::new(directory).clear()?;
+        }
+        let files = command.changed_since.is_some() || command.staged || command.watch.is_some();
+        if command.path.is_none() && !command.use_std_in && !files {
+            return Ok(());
+        }
+    }
+
+    if command.changed_since.is_some() || command.staged {
+        return format_changed_files(command);
+    }
+    if !command.paths.is_empty() {
+        return format_files(command);
+    }
+    if let Some(paths) = &command.watch {
+        let mut command = command.clone();
+        command.clear_cache = false;
+        return watch::watch(&command, paths);
+    }
+
+    let document = document_path(command)?;
+    let (settings, configuration) = resolve_settings(command, &document)?;
+
     if command.save_configuration {
         std::fs::write(CONFIG_NAME, toml::to_string_pretty(&settings)?)
             .map_err(FormatError::FailedToSaveConfigurationFile)?;
         return Ok(());
     }
 
+    // standard input is written to standard output without an output file
+    let std_out =
+        command.use_std_out || (command.use_std_in && command.output.is_none() && !command.check);
+    if command.report.is_some() && std_out {
+        return Err(FormatError::ReportAndStdOutSpecified);
+    }
+
+    let start = Instant::now();
     let (input_data, input_name) = match (&command.path, command.use_std_in) {
         (Some(_), true) => return Err(FormatError::InputFileAndStdInSpecified),
         (Some(path), false) => {
@@ -163,36 +552,301 @@ pub fn format(command: &Command) -> Result<(), FormatError> {
             std::io::stdin()
                 .read_to_string(&mut data)
                 .map_err(FormatError::FailedToReadStdIn)?;
-            (data, "stdin".into())
+            let name = match &command.stdin_filepath {
+                Some(path) => path.display().to_string(),
+                None => STDIN_NAME.into(),
+            };
+            (data, name)
         }
         (None, false) => return Err(FormatError::NoInputFileOrStdInSpecified),
     };
 
-    let root = typst_syntax::parse(&input_data);
+    // only whole files are cached, standard input is usually formatted by editors
+    let cache = match (&cache_dir, &command.path) {
+        (Some(directory), Some(_)) if !command.no_cache && command.lines.is_none() => {
+            Some(Cache::new(directory))
+        }
+        _ => None,
+    };
+    let key = Cache::key(&input_data, &settings);
+
+    let (output, errors) = match &cache {
+        Some(cache) if cache.contains(&key) => (Cow::Borrowed(input_data.as_str()), Vec::new()),
+        _ => {
+            let (output, errors) = match &command.lines {
+                Some(lines) => format_lines(&input_data, &settings, lines),
+                None => {
+                    let mut output = Vec::new();
+                    let errors = format_str(&input_data, &settings, &mut output);
+                    (String::from_utf8_lossy(&output).into_owned(), errors)
+                }
+            };
+            if command.report.is_none() {
+                for error in errors.iter() {
+                    eprint!("{}", error.report(&input_name, &input_data));
+                }
+            }
+            (Cow::Owned(output), errors)
+        }
+    };
 
-    match (&command.output, command.use_std_out) {
-        (Some(_), true) => return Err(FormatError::OutputFileAndStdOutSpecified),
-        (Some(out), false) => {
-            let file = File::create(out).map_err(FormatError::FailedToCreateOutputFile)?;
-            let mut target = BufWriter::new(file);
-            format_node(&root, &settings, &mut target);
-            drop(target);
+    let error_count = errors.len();
+    let failed = command.fail_on_parse_error && error_count != 0;
+    let changed = input_data != output;
+    let written = !failed && !command.check && !command.diff;
+    if written {
+        write_output(command, &output, changed)?;
+    }
+    if let Some(cache) = &cache
+        && error_count == 0
+    {
+        match (changed, written) {
+            (false, _) => cache.insert(&key),
+            // the next run reads the written output
+            (true, true) => cache.insert(&Cache::key(&output, &settings)),
+            (true, false) => {}
         }
-        (None, true) => {
-            let mut target = BufWriter::new(std::io::stdout());
-            format_node(&root, &settings, &mut target);
-            drop(target);
+    }
+    if command.diff && changed {
+        let diff = TextDiff::from_lines(input_data.as_str(), &output);
+        print!("{}", diff.unified_diff().header(&input_name, &input_name));
+    }
+
+    if let Some(ReportFormat::Json) = command.report {
+        let report = FileReport::new(
+            input_name.clone(),
+            &input_data,
+            &output,
+            errors,
+            configuration,
+            start.elapsed(),
+        );
+        println!("{}", serde_json::to_string(&report)?);
+    }
+
+    if failed {
+        return Err(FormatError::ParseErrors(error_count));
+    }
+    if (command.check || command.diff) && changed {
+        return Err(FormatError::NotFormatted(input_name));
+    }
+    Ok(())
+}
+
+/// Settings for the document, with the used configuration file.
+fn resolve_settings(
+    command: &Command,
+    document: &Path,
+) -> Result<(Settings, Option<PathBuf>), FormatError> {
+    let mut settings = command.style.settings();
+
+    if command.use_editorconfig {
+        editorconfig::apply(&mut settings, document)?;
+    }
+
+    let mut configuration = None;
+    if command.use_configuration {
+        let file = find_configuration(document)?;
+        settings.overwrite(&file)?;
+        configuration = Some(file);
+    }
+
+    settings.overwrite_values(&command.settings)?;
+    Ok((settings, configuration))
+}
+
+/// Nearest 'prettypst.toml' in the folders of the document.
+fn find_configuration(document: &Path) -> Result<PathBuf, FormatError> {
+    let mut path = document;
+    loop {
+        path = path.parent().ok_or(FormatError::NoConfigurationFile)?;
+        let file = path.join(CONFIG_NAME);
+        if file.is_file() {
+            return Ok(file);
         }
-        (None, false) => {
-            let temp_path = format!("{}.tmp", input_name);
-            let file =
-                File::create(&temp_path).map_err(FormatError::FailedToCreateTemporaryFile)?;
-            let mut target = BufWriter::new(file);
-            format_node(&root, &settings, &mut target);
-            drop(target);
+    }
+}
 
-            fs::rename(temp_path, input_name).map_err(FormatError::FailedToReplaceInputFile)?;
+/// Format every changed file on its own and collect the errors.
+fn format_changed_files(command: &Command) -> Result<(), FormatError> {
+    let files = git::changed_files(command.changed_since.as_deref(), command.staged)?;
+    let mut errors = Vec::new();
+    for file in files {
+        let mut file_command = command.clone();
+        file_command.path = Some(file);
+        file_command.changed_since = None;
+        file_command.staged = false;
+        file_command.clear_cache = false;
+        let revision = command.changed_since.as_deref();
+        if let Err(err) = format_changed_file(&mut file_command, revision, command.staged) {
+            errors.push(err);
         }
+    }
+    collect_errors(errors)
+}
+
+/// Format every input file on its own and collect the errors.
+fn format_files(command: &Command) -> Result<(), FormatError> {
+    let mut errors = Vec::new();
+    for file in command.path.iter().chain(&command.paths) {
+        let mut file_command = command.clone();
+        file_command.path = Some(file.clone());
+        file_command.paths = Vec::new();
+        file_command.clear_cache = false;
+        if let Err(err) = format(&file_command) {
+            errors.push(err);
+        }
+    }
+    collect_errors(errors)
+}
+
+fn collect_errors(mut errors: Vec<FormatError>) -> Result<(), FormatError> {
+    match errors.len() {
+        0 => Ok(()),
+        1 => Err(errors.remove(0)),
+        _ => Err(FormatError::Files(errors)),
+    }
+}
+
+fn format_changed_file(
+    command: &mut Command,
+    revision: Option<&str>,
+    staged: bool,
+) -> Result<(), FormatError> {
+    let Some(path) = command.path.clone() else {
+        return Ok(());
     };
+    let path = path.as_path();
+    // partially staged files keep their unstaged changes out of the index
+    let partial = staged && git::has_unstaged_changes(path)?;
+    if partial {
+        let document = document_path(command)?;
+        let (settings, _) = resolve_settings(command, &document)?;
+        let content = git::staged_content(path)?;
+        let (output, errors) = if command.only_changed_lines {
+            let lines = git::changed_lines(path, revision, true)?;
+            format_lines(&content, &settings, &lines)
+        } else {
+            let mut output = Vec::new();
+            let errors = format_str(&content, &settings, &mut output);
+            (String::from_utf8_lossy(&output).into_owned(), errors)
+        };
+        let failed = command.fail_on_parse_error && !errors.is_empty();
+        if command.check {
+            // only the index is committed, the working tree is not checked
+            return match (failed, output != content) {
+                (true, _) => Err(FormatError::ParseErrors(errors.len())),
+                (false, true) => Err(FormatError::NotFormatted(path.display().to_string())),
+                (false, false) => Ok(()),
+            };
+        }
+        if !failed && output != content {
+            git::stage_content(path, &output)?;
+        }
+    }
+    if command.only_changed_lines {
+        // the working tree is compared with the revision or the last commit for staged files
+        let revision = revision.or(staged.then_some("HEAD"));
+        command.lines = Some(git::changed_lines(path, revision, false)?);
+    }
+    format(command)?;
+    if staged && !partial && !command.check {
+        git::stage(path)?;
+    }
     Ok(())
 }
+
+fn write_output(command: &Command, output: &str, changed: bool) -> Result<(), FormatError> {
+    match (&command.output, command.use_std_out, &command.path) {
+        (Some(_), true, _) => return Err(FormatError::OutputFileAndStdOutSpecified),
+        (Some(out), false, _) => {
+            fs::write(out, output).map_err(FormatError::FailedToCreateOutputFile)?
+        }
+        // the path for standard input is only a hint and never written to
+        (None, true, _) | (None, false, None) => std::io::stdout()
+            .write_all(output.as_bytes())
+            .map_err(FormatError::FailedToWriteStdOut)?,
+        (None, false, Some(path)) => {
+            // keep the modification time for unchanged files
+            if changed {
+                replace_file(path, output)?;
+            }
+        }
+    };
+    Ok(())
+}
+
+/// Replace the file with a renamed temporary file, symbolic links are followed and permissions are kept.
+fn replace_file(path: &Path, data: &str) -> Result<(), FormatError> {
+    let target = fs::canonicalize(path).map_err(FormatError::FailedToReplaceInputFile)?;
+    let permissions = fs::metadata(&target)
+        .map_err(FormatError::FailedToReplaceInputFile)?
+        .permissions();
+
+    let (temp_path, mut file) = create_temporary_file(&target)?;
+    // restrict the permissions before the content is visible
+    let result = file
+        .set_permissions(permissions)
+        .and_then(|()| file.write_all(data.as_bytes()))
+        .and_then(|()| file.sync_all())
+        .map_err(FormatError::FailedToCreateTemporaryFile)
+        .and_then(|()| {
+            fs::rename(&temp_path, &target).map_err(FormatError::FailedToReplaceInputFile)
+        });
+    drop(file);
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    result
+}
+
+fn create_temporary_file(target: &Path) -> Result<(PathBuf, File), FormatError> {
+    let name = target.file_name().unwrap_or_default().to_string_lossy();
+    let mut index = 0;
+    loop {
+        let path = target.with_file_name(format!(".{name}.{}-{index}.tmp", std::process::id()));
+        match File::options().write(true).create_new(true).open(&path) {
+            Ok(file) => return Ok((path, file)),
+            Err(err) if err.kind() == ErrorKind::AlreadyExists => {
+                if index >= 100 {
+                    return Err(FormatError::FailedToGetTemporaryFilePath(err));
+                }
+                index += 1;
+            }
+            Err(err) => return Err(FormatError::FailedToCreateTemporaryFile(err)),
+        }
+    }
+}
+
+fn parse_line_range(value: &str) -> Result<LineRange, String> {
+    let (start, end) = value.split_once('-').unwrap_or((value, value));
+    let parse = |number: &str| {
+        number
+            .trim()
+            .parse::<usize>()
+            .ok()
+            .filter(|&number| number > 0)
+            .ok_or_else(|| format!("invalid line number '{number}'"))
+    };
+    let (start, end) = (parse(start)?, parse(end)?);
+    if end < start {
+        return Err(format!("invalid line range '{value}'"));
+    }
+    Ok(LineRange { start, end })
+}
+
+/// Path of the formatted document, which might not exist for standard input.
+fn document_path(command: &Command) -> Result<PathBuf, FormatError> {
+    let path = match (
+        &command.stdin_filepath,
+        &command.file_location,
+        &command.path,
+    ) {
+        (Some(path), _, _) => path.to_owned(),
+        (_, Some(path), _) if path.extension().is_some() => path.to_owned(),
+        (_, Some(path), _) => path.join(STDIN_DOCUMENT),
+        (_, _, Some(path)) => path.to_owned(),
+        _ => Path::new(STDIN_DOCUMENT).to_owned(),
+    };
+    std::path::absolute(path).map_err(FormatError::FailedToGetWorkingDirectory)
+}
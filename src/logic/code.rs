@@ -3,6 +3,8 @@ use std::{
     ops::Not,
 };
 
+use typst_syntax::ast::{Assoc, BinOp, UnOp};
+
 use super::*;
 
 pub fn format_code_block(
@@ -26,7 +28,11 @@ pub fn format_code_block(
                     output.set_whitespace(Whitespace::LineBreak, Priority::Normal);
                 }
             }
-            SyntaxKind::Code => format(child, state, settings, output),
+            SyntaxKind::Code => {
+                output.begin_node(child);
+                format_code(child, state, settings, output, !single);
+                output.end_node(child);
+            }
             SyntaxKind::RightBrace => {
                 if single {
                     output.set_whitespace(Whitespace::Space, Priority::Low);
@@ -47,13 +53,16 @@ pub fn format_func_call(
     settings: &Settings,
     output: &mut Output<impl OutputTarget>,
 ) {
+    if let Some(chain) = Chain::new(node, state, settings) {
+        return chain.format(state, settings, output);
+    }
     #[derive(Debug)]
     enum Kind<'a> {
         Normal,
         Columns(&'a str),
     }
     let mut kind = Kind::Normal;
-    for child in node.children() {
+    for (index, child) in node.children().enumerate() {
         match child.kind() {
             SyntaxKind::Ident => {
                 kind = match settings.columns_methods.get(child.text().as_str()) {
@@ -62,6 +71,9 @@ pub fn format_func_call(
                 };
                 format(child, state, settings, output);
             }
+            _ if keeps_parentheses(node, index, child) => {
+                format_kept_parentheses(child, state, settings, output)
+            }
             SyntaxKind::Args => match kind {
                 Kind::Normal => format_items(child, state, settings, output),
                 Kind::Columns(column_argument) => {
@@ -73,6 +85,500 @@ pub fn format_func_call(
     }
 }
 
+pub fn format_field_access(
+    node: &SyntaxNode,
+    state: State,
+    settings: &Settings,
+    output: &mut Output<impl OutputTarget>,
+) {
+    match Chain::new(node, state, settings) {
+        Some(chain) => chain.format(state, settings, output),
+        None => format_default(node, state, settings, output),
+    }
+}
+
+struct Chain<'a> {
+    base: &'a SyntaxNode,
+    links: Vec<Link<'a>>,
+}
+
+struct Link<'a> {
+    dot: &'a SyntaxNode,
+    name: &'a SyntaxNode,
+    args: Option<&'a SyntaxNode>,
+    nodes: Vec<&'a SyntaxNode>,
+}
+
+impl<'a> Chain<'a> {
+    fn new(node: &'a SyntaxNode, state: State, settings: &Settings) -> Option<Self> {
+        if matches!(state.mode, Mode::Math)
+            || settings.max_width(settings.chain.max_width) == 0
+            || state.flat
+        {
+            return None;
+        }
+        let mut links = Vec::new();
+        let base = flatten_chain(node, &mut links)?;
+        let calls = links.iter().filter(|link| link.args.is_some()).count();
+        if calls == 0 || calls < settings.chain.min_calls {
+            return None;
+        }
+        // the outermost node is handled by `format`
+        if let Some(last) = links.last_mut() {
+            last.nodes.retain(|inner| !std::ptr::eq(*inner, node));
+        }
+        Some(Self { base, links })
+    }
+
+    fn format(
+        &self,
+        mut state: State,
+        settings: &Settings,
+        output: &mut Output<impl OutputTarget>,
+    ) {
+        let fixpoint = output.create_fixpoint();
+        let start_line = output.position().0;
+        self.format_links(
+            State {
+                flat: true,
+                ..state
+            },
+            settings,
+            output,
+            false,
+        );
+        let (line, column) = output.position();
+        output.set_fixpoint(fixpoint);
+
+        let broken = line != start_line || column > settings.max_width(settings.chain.max_width);
+        // in markup a linebreak would end the embedded expression
+        let parenthesize = broken && matches!(state.mode, Mode::Markup | Mode::MarkupBreakable);
+        if parenthesize {
+            output.raw_text("(", &state, settings);
+            output.set_whitespace(Whitespace::None, Priority::High);
+            state.mode = Mode::Items;
+        }
+        self.format_links(state, settings, output, broken);
+        if parenthesize {
+            output.set_whitespace(Whitespace::None, Priority::High);
+            output.raw_text(")", &state, settings);
+        }
+    }
+
+    fn format_links(
+        &self,
+        mut state: State,
+        settings: &Settings,
+        output: &mut Output<impl OutputTarget>,
+        broken: bool,
+    ) {
+        for link in &self.links {
+            for inner in &link.nodes {
+                output.begin_node(inner);
+            }
+        }
+        format(self.base, state, settings, output);
+        if broken {
+            state.indent();
+        }
+        for link in &self.links {
+            if broken {
+                output.set_whitespace(Whitespace::LineBreak, Priority::High);
+            }
+            format(link.dot, state, settings, output);
+            format(link.name, state, settings, output);
+            if let Some(args) = link.args {
+                format_items(args, state, settings, output);
+            }
+            for inner in &link.nodes {
+                output.end_node(inner);
+            }
+        }
+    }
+}
+
+fn flatten_chain<'a>(node: &'a SyntaxNode, links: &mut Vec<Link<'a>>) -> Option<&'a SyntaxNode> {
+    match node.kind() {
+        SyntaxKind::FuncCall => {
+            let mut children = node.children();
+            let (Some(callee), Some(args), None) =
+                (children.next(), children.next(), children.next())
+            else {
+                return None;
+            };
+            if callee.kind() != SyntaxKind::FieldAccess {
+                return Some(node);
+            }
+            let base = flatten_chain(callee, links)?;
+            let link = links.last_mut()?;
+            link.args = Some(args);
+            link.nodes.push(node);
+            Some(base)
+        }
+        SyntaxKind::FieldAccess => {
+            let (mut target, mut dot, mut name) = (None, None, None);
+            for child in node.children() {
+                match child.kind() {
+                    SyntaxKind::Space => {}
+                    SyntaxKind::Dot if target.is_some() => dot = Some(child),
+                    SyntaxKind::Ident if dot.is_some() => name = Some(child),
+                    _ if target.is_none() => target = Some(child),
+                    _ => return None,
+                }
+            }
+            let base = flatten_chain(target?, links)?;
+            links.push(Link {
+                dot: dot?,
+                name: name?,
+                args: None,
+                nodes: vec![node],
+            });
+            Some(base)
+        }
+        _ => Some(node),
+    }
+}
+
+pub fn format_parenthesized(
+    node: &SyntaxNode,
+    state: State,
+    settings: &Settings,
+    output: &mut Output<impl OutputTarget>,
+) {
+    match redundant_parentheses(node, state, settings, output) {
+        Some(inner) => format(inner, state, settings, output),
+        None => format_parentheses(node, state, settings, output),
+    }
+}
+
+fn format_parentheses(
+    node: &SyntaxNode,
+    mut state: State,
+    settings: &Settings,
+    output: &mut Output<impl OutputTarget>,
+) {
+    state.mode = Mode::Items;
+    format_default(node, state, settings, output);
+}
+
+fn redundant_parentheses<'a>(
+    node: &'a SyntaxNode,
+    state: State,
+    settings: &Settings,
+    output: &Output<impl OutputTarget>,
+) -> Option<&'a SyntaxNode> {
+    if !settings.parentheses.remove_redundant {
+        return None;
+    }
+    let inner = parenthesized_expression(node)?;
+    let items = matches!(state.mode, Mode::Items | Mode::MultilineItems);
+    match inner.kind() {
+        SyntaxKind::Parenthesized => Some(inner),
+        // an embedded expression like `#(x)y` would continue differently
+        _ if output.last_char() == Some('#') => None,
+        SyntaxKind::FuncCall | SyntaxKind::FieldAccess if !items && contains_linebreak(inner) => {
+            None
+        }
+        SyntaxKind::Ident
+        | SyntaxKind::None
+        | SyntaxKind::Auto
+        | SyntaxKind::Bool
+        | SyntaxKind::Int
+        | SyntaxKind::Float
+        | SyntaxKind::Numeric
+        | SyntaxKind::Str
+        | SyntaxKind::Array
+        | SyntaxKind::Dict
+        | SyntaxKind::CodeBlock
+        | SyntaxKind::ContentBlock
+        | SyntaxKind::FuncCall
+        | SyntaxKind::FieldAccess => Some(inner),
+        _ => None,
+    }
+}
+
+fn parenthesized_expression(node: &SyntaxNode) -> Option<&SyntaxNode> {
+    if node.kind() != SyntaxKind::Parenthesized {
+        return None;
+    }
+    let mut expressions = node.children().filter(|child| {
+        !matches!(
+            child.kind(),
+            SyntaxKind::LeftParen | SyntaxKind::RightParen | SyntaxKind::Space
+        )
+    });
+    match (expressions.next(), expressions.next()) {
+        (Some(inner), None) if !inner.kind().is_trivia() => Some(inner),
+        _ => None,
+    }
+}
+
+fn keeps_parentheses(parent: &SyntaxNode, index: usize, child: &SyntaxNode) -> bool {
+    let Some(inner) = parenthesized_expression(child).filter(|_| index == 0) else {
+        return false;
+    };
+    // `(a.b)(x)` calls the field and `(a): x` uses the value of `a` as key
+    match parent.kind() {
+        SyntaxKind::FuncCall => matches!(
+            inner.kind(),
+            SyntaxKind::FieldAccess | SyntaxKind::Parenthesized
+        ),
+        SyntaxKind::Keyed => true,
+        _ => false,
+    }
+}
+
+fn format_kept_parentheses(
+    node: &SyntaxNode,
+    state: State,
+    settings: &Settings,
+    output: &mut Output<impl OutputTarget>,
+) {
+    output.begin_node(node);
+    format_parentheses(node, state, settings, output);
+    output.end_node(node);
+}
+
+pub fn format_binary(
+    node: &SyntaxNode,
+    mut state: State,
+    settings: &Settings,
+    output: &mut Output<impl OutputTarget>,
+) {
+    let mut operands = Vec::new();
+    let mut operators = Vec::new();
+    let max_width = settings.max_width(settings.binary.max_width);
+    if max_width == 0 || state.flat || flatten_binary(node, &mut operands, &mut operators).is_none()
+    {
+        return format_binary_default(node, state, settings, output);
+    }
+
+    let fixpoint = output.create_fixpoint();
+    let start_line = output.position().0;
+    format_binary_default(
+        node,
+        State {
+            flat: true,
+            ..state
+        },
+        settings,
+        output,
+    );
+    let (line, column) = output.position();
+    output.set_fixpoint(fixpoint);
+    // expressions with multiline operands like content blocks keep their layout
+    if line != start_line || column <= max_width {
+        return format_binary_default(node, state, settings, output);
+    }
+
+    // outside of parentheses a linebreak would end the expression
+    let parenthesize = !matches!(state.mode, Mode::Items | Mode::MultilineItems);
+    if parenthesize {
+        output.raw_text("(", &state, settings);
+        output.set_whitespace(Whitespace::None, Priority::High);
+    }
+    state.mode = Mode::Items;
+    state.indent();
+
+    let mut operands = operands.into_iter();
+    if let Some(first) = operands.next() {
+        format_operand(node, first, true, state, settings, output);
+    }
+    for (operator, operand) in operators.into_iter().zip(operands) {
+        let (before, after) = match settings.binary.operator_position {
+            OperatorPosition::Leading => (Whitespace::LineBreak, Whitespace::Space),
+            OperatorPosition::Trailing => (Whitespace::Space, Whitespace::LineBreak),
+        };
+        output.set_whitespace(before, Priority::High);
+        for (index, token) in operator.into_iter().enumerate() {
+            if index > 0 {
+                output.set_whitespace(Whitespace::Space, Priority::High);
+            }
+            format(token, state, settings, output);
+        }
+        output.set_whitespace(after, Priority::High);
+        format_operand(node, operand, false, state, settings, output);
+    }
+
+    if parenthesize {
+        output.set_whitespace(Whitespace::None, Priority::High);
+        output.raw_text(")", &state, settings);
+    }
+}
+
+fn format_binary_default(
+    node: &SyntaxNode,
+    state: State,
+    settings: &Settings,
+    output: &mut Output<impl OutputTarget>,
+) {
+    let mut lhs = true;
+    for child in node.children() {
+        match child.kind() {
+            SyntaxKind::Space | SyntaxKind::LineComment | SyntaxKind::BlockComment => {
+                format(child, state, settings, output)
+            }
+            kind if is_binary_operator(kind) => {
+                lhs = false;
+                format(child, state, settings, output);
+            }
+            _ => format_operand(node, child, lhs, state, settings, output),
+        }
+    }
+}
+
+fn format_operand(
+    parent: &SyntaxNode,
+    operand: &SyntaxNode,
+    lhs: bool,
+    state: State,
+    settings: &Settings,
+    output: &mut Output<impl OutputTarget>,
+) {
+    let parentheses = &settings.parentheses;
+    if parentheses.clarify_mixed_logic && mixed_logic(parent, operand) {
+        output.raw_text("(", &state, settings);
+        output.set_whitespace(Whitespace::None, Priority::High);
+        format(operand, state, settings, output);
+        output.set_whitespace(Whitespace::None, Priority::High);
+        output.raw_text(")", &state, settings);
+        return;
+    }
+
+    let inner = match operand.kind() {
+        SyntaxKind::Parenthesized if parentheses.remove_redundant => {
+            parenthesized_expression(operand)
+        }
+        _ => None,
+    };
+    let Some(inner) = inner.filter(|&inner| {
+        !(parentheses.clarify_mixed_logic && mixed_logic(parent, inner))
+            && (matches!(state.mode, Mode::Items | Mode::MultilineItems)
+                || !contains_linebreak(inner))
+            && binds_tighter(parent, inner, lhs)
+    }) else {
+        return format(operand, state, settings, output);
+    };
+    output.begin_node(operand);
+    format(inner, state, settings, output);
+    output.end_node(operand);
+}
+
+fn mixed_logic(parent: &SyntaxNode, operand: &SyntaxNode) -> bool {
+    let operator = |node| binary_operator(node).map(SyntaxNode::kind);
+    operator(parent) == Some(SyntaxKind::Or) && operator(operand) == Some(SyntaxKind::And)
+}
+
+fn binds_tighter(parent: &SyntaxNode, inner: &SyntaxNode, lhs: bool) -> bool {
+    let Some(operator) =
+        binary_operator(parent).and_then(|operator| BinOp::from_kind(operator.kind()))
+    else {
+        return false;
+    };
+    let precedence = operator.precedence();
+    match inner.kind() {
+        SyntaxKind::Binary => match binary_precedence(inner) {
+            Some(inner) if inner > precedence => true,
+            Some(inner) => inner == precedence && lhs && operator.assoc() == Assoc::Left,
+            None => false,
+        },
+        SyntaxKind::Unary => inner
+            .children()
+            .find_map(|child| UnOp::from_kind(child.kind()))
+            .is_some_and(|unary| unary.precedence() > precedence),
+        _ => false,
+    }
+}
+
+fn flatten_binary<'a>(
+    node: &'a SyntaxNode,
+    operands: &mut Vec<&'a SyntaxNode>,
+    operators: &mut Vec<Vec<&'a SyntaxNode>>,
+) -> Option<()> {
+    let precedence = binary_precedence(node)?;
+    if precedence <= BinOp::Assign.precedence() {
+        return None;
+    }
+
+    let mut children = node
+        .children()
+        .filter(|child| child.kind() != SyntaxKind::Space);
+    let lhs = children.next()?;
+    if binary_precedence(lhs) == Some(precedence) {
+        flatten_binary(lhs, operands, operators)?;
+    } else {
+        operands.push(lhs);
+    }
+
+    let mut operator = Vec::new();
+    let mut rhs = None;
+    for child in children {
+        match child.kind() {
+            SyntaxKind::LineComment | SyntaxKind::BlockComment => return None,
+            _ if rhs.is_some() => return None,
+            kind if is_binary_operator(kind) => operator.push(child),
+            _ => rhs = Some(child),
+        }
+    }
+    operators.push(operator);
+    operands.push(rhs?);
+    Some(())
+}
+
+fn binary_precedence(node: &SyntaxNode) -> Option<u8> {
+    match binary_operator(node)?.kind() {
+        SyntaxKind::Not => Some(BinOp::NotIn.precedence()),
+        kind => BinOp::from_kind(kind).map(BinOp::precedence),
+    }
+}
+
+fn binary_operator(node: &SyntaxNode) -> Option<&SyntaxNode> {
+    if node.kind() != SyntaxKind::Binary {
+        return None;
+    }
+    node.children()
+        .skip(1)
+        .find(|child| is_binary_operator(child.kind()))
+}
+
+fn is_binary_operator(kind: SyntaxKind) -> bool {
+    kind == SyntaxKind::Not || BinOp::from_kind(kind).is_some()
+}
+
+/// Conditionals and loops with one space between their parts.
+pub fn format_control_flow(
+    node: &SyntaxNode,
+    state: State,
+    settings: &Settings,
+    output: &mut Output<impl OutputTarget>,
+) {
+    if node.children().any(|child| {
+        matches!(
+            child.kind(),
+            SyntaxKind::LineComment | SyntaxKind::BlockComment
+        )
+    }) {
+        return format_default(node, state, settings, output);
+    }
+    // in markup a linebreak would end the conditional before the 'else'
+    let else_on_new_line = matches!(settings.control_flow.else_position, ElsePosition::NewLine)
+        && matches!(state.mode, Mode::Code | Mode::Items | Mode::MultilineItems);
+
+    let parts = node
+        .children()
+        .filter(|child| child.kind() != SyntaxKind::Space);
+    for (index, child) in parts.enumerate() {
+        match child.kind() {
+            SyntaxKind::Else if else_on_new_line => {
+                output.set_whitespace(Whitespace::LineBreak, Priority::High)
+            }
+            _ if index > 0 => output.set_whitespace(Whitespace::Space, Priority::High),
+            _ => {}
+        }
+        format(child, state, settings, output);
+    }
+}
+
 pub fn format_unary(
     node: &SyntaxNode,
     state: State,
@@ -112,11 +618,14 @@ pub fn format_keyed(
     settings: &Settings,
     output: &mut Output<impl OutputTarget>,
 ) {
-    for child in node.children() {
+    for (index, child) in node.children().enumerate() {
         match child.kind() {
             SyntaxKind::Colon => {
                 format_optional_padding(child, state, settings, output, &settings.dictionary_entry);
             }
+            _ if keeps_parentheses(node, index, child) => {
+                format_kept_parentheses(child, state, settings, output)
+            }
             _ => format(child, state, settings, output),
         }
     }
@@ -138,27 +647,56 @@ pub fn format_items(
     settings: &Settings,
     output: &mut Output<impl OutputTarget>,
 ) {
-    let mut trailing_comma = false;
+    let mut trailing_comma = None;
     let mut comma_count = 0;
-    for child in node.children() {
+    let mut line_comment = false;
+    for (index, child) in node.children().enumerate() {
         match child.kind() {
-            SyntaxKind::Comma => (trailing_comma, comma_count) = (true, comma_count + 1),
+            SyntaxKind::Comma => (trailing_comma, comma_count) = (Some(index), comma_count + 1),
             SyntaxKind::RightParen => break,
-            SyntaxKind::Space | SyntaxKind::LineComment | SyntaxKind::BlockComment => {}
-            _ => trailing_comma = false,
+            SyntaxKind::LineComment => line_comment = true,
+            SyntaxKind::Space | SyntaxKind::BlockComment => {}
+            _ => trailing_comma = None,
         }
     }
 
     let force_single_inline = matches!(node.kind(), SyntaxKind::Array);
-    let single = !trailing_comma || (force_single_inline && comma_count <= 1);
+    // a line comment would swallow the rest of the items
+    let collapse = !settings.items.expand_trailing_comma && !line_comment;
+    // `map(x => {` ... `})` instead of one line for the closure
+    let hug = node.kind() == SyntaxKind::Args
+        && settings.closure.hug_last_argument
+        && !line_comment
+        && last_item(node)
+            .is_some_and(|item| item.kind() == SyntaxKind::Closure && contains_linebreak(item));
+    let single =
+        trailing_comma.is_none() || collapse || hug || (force_single_inline && comma_count <= 1);
     state.mode = if single {
         Mode::Items
     } else {
         Mode::MultilineItems
     };
 
-    for child in node.children() {
+    // the trailing comma is required for arrays and destructurings with one item
+    let skipped_comma = match node.kind() {
+        SyntaxKind::Args | SyntaxKind::Params | SyntaxKind::Dict if single => trailing_comma,
+        _ => None,
+    };
+
+    let children = node.children().collect::<Vec<_>>();
+    let padding = match node.kind() {
+        SyntaxKind::Args | SyntaxKind::Dict if settings.alignment.named_entries && !single => {
+            alignment_padding(&children, entry_key, state, settings, output)
+        }
+        _ => vec![0; children.len()],
+    };
+
+    for (index, child) in node.children().enumerate() {
         match child.kind() {
+            SyntaxKind::Comma if skipped_comma == Some(index) => {}
+            SyntaxKind::Named | SyntaxKind::Keyed if padding[index] > 0 => {
+                format_aligned_entry(child, state, settings, output, padding[index])
+            }
             SyntaxKind::LeftParen => {
                 format(child, state, settings, output);
                 if single {
@@ -190,6 +728,242 @@ pub fn format_items(
     }
 }
 
+/// Spaces after the key of every child which align the separators of consecutive entries.
+///
+/// Groups end at blank lines, comments and children without a single line key.
+fn alignment_padding<'a>(
+    children: &[&'a SyntaxNode],
+    key: impl Fn(&'a SyntaxNode) -> Option<&'a SyntaxNode>,
+    state: State,
+    settings: &Settings,
+    output: &mut Output<impl OutputTarget>,
+) -> Vec<usize> {
+    fn finish(group: &mut Vec<(usize, usize)>, padding: &mut [usize]) {
+        let widest = group.iter().map(|&(_, width)| width).max().unwrap_or(0);
+        for (index, width) in group.drain(..) {
+            padding[index] = widest - width;
+        }
+    }
+
+    // measured without the whitespace in front of the children
+    let fixpoint = output.create_fixpoint();
+    output.set_whitespace(Whitespace::None, Priority::Guaranteed);
+
+    let mut padding = vec![0; children.len()];
+    let mut group = Vec::new();
+    for (index, &child) in children.iter().enumerate() {
+        match child.kind() {
+            SyntaxKind::Space if child.text().matches('\n').count() <= 1 => {}
+            SyntaxKind::Comma | SyntaxKind::Hash => {}
+            _ => match key(child).and_then(|key| get_length(key, state, settings, output)) {
+                Some(width) => group.push((index, width)),
+                None => finish(&mut group, &mut padding),
+            },
+        }
+    }
+    finish(&mut group, &mut padding);
+    output.set_fixpoint(fixpoint);
+    padding
+}
+
+fn entry_key(node: &SyntaxNode) -> Option<&SyntaxNode> {
+    match node.kind() {
+        SyntaxKind::Named | SyntaxKind::Keyed => node.children().next(),
+        _ => None,
+    }
+}
+
+/// Pattern of a let binding with a value, function definitions have no pattern.
+fn let_binding_pattern(node: &SyntaxNode) -> Option<&SyntaxNode> {
+    if node.kind() != SyntaxKind::LetBinding
+        || !node.children().any(|child| child.kind() == SyntaxKind::Eq)
+    {
+        return None;
+    }
+    node.children()
+        .find(|child| !matches!(child.kind(), SyntaxKind::Let | SyntaxKind::Space))
+}
+
+fn format_aligned_entry(
+    node: &SyntaxNode,
+    state: State,
+    settings: &Settings,
+    output: &mut Output<impl OutputTarget>,
+    padding: usize,
+) {
+    let spacing = match node.kind() {
+        SyntaxKind::Keyed => &settings.dictionary_entry,
+        _ => &settings.named_argument,
+    };
+    let mut aligned = false;
+    for (index, child) in node.children().enumerate() {
+        match child.kind() {
+            SyntaxKind::Colon if !aligned => {
+                format_optional_padding(child, state, settings, output, spacing);
+                let spaces = padding + usize::from(spacing.space_after);
+                output.set_whitespace(Whitespace::Spaces(spaces), Priority::High);
+                aligned = true;
+            }
+            _ if keeps_parentheses(node, index, child) => {
+                format_kept_parentheses(child, state, settings, output)
+            }
+            _ => format(child, state, settings, output),
+        }
+    }
+}
+
+/// Code statements with the `=` of consecutive let bindings aligned.
+pub fn format_code(
+    node: &SyntaxNode,
+    state: State,
+    settings: &Settings,
+    output: &mut Output<impl OutputTarget>,
+    multiline: bool,
+) {
+    let children = node.children().collect::<Vec<_>>();
+    let padding = let_binding_padding(&children, state, settings, output);
+    for (index, (&child, padding)) in children.iter().zip(padding).enumerate() {
+        match child.kind() {
+            SyntaxKind::Semicolon => match semicolon_separator(&children[index + 1..]) {
+                Separator::EndOfLine if settings.semicolons.remove_trailing => {}
+                Separator::Statement if multiline && settings.semicolons.split_statements => {
+                    output.set_whitespace(Whitespace::LineBreak, Priority::High)
+                }
+                _ => format(child, state, settings, output),
+            },
+            _ if padding > 0 => format_aligned_let_binding(child, state, settings, output, padding),
+            _ => format(child, state, settings, output),
+        }
+    }
+}
+
+enum Separator {
+    EndOfLine,
+    Statement,
+    Other,
+}
+
+fn semicolon_separator(after: &[&SyntaxNode]) -> Separator {
+    let mut after = after
+        .iter()
+        .skip_while(|child| child.kind() == SyntaxKind::Space && !child.text().contains('\n'));
+    match after.next().map(|child| child.kind()) {
+        None | Some(SyntaxKind::Space | SyntaxKind::LineComment) => Separator::EndOfLine,
+        Some(SyntaxKind::Semicolon | SyntaxKind::BlockComment) => Separator::Other,
+        Some(_) => Separator::Statement,
+    }
+}
+
+/// Spaces before the `=` of every let binding in `children`, zero if alignment is disabled.
+pub fn let_binding_padding(
+    children: &[&SyntaxNode],
+    state: State,
+    settings: &Settings,
+    output: &mut Output<impl OutputTarget>,
+) -> Vec<usize> {
+    match settings.alignment.let_bindings {
+        true => alignment_padding(children, let_binding_pattern, state, settings, output),
+        false => vec![0; children.len()],
+    }
+}
+
+pub fn format_aligned_let_binding(
+    node: &SyntaxNode,
+    state: State,
+    settings: &Settings,
+    output: &mut Output<impl OutputTarget>,
+    padding: usize,
+) {
+    output.begin_node(node);
+    for child in node.children() {
+        if child.kind() == SyntaxKind::Eq {
+            output.set_whitespace(Whitespace::Spaces(padding + 1), Priority::High);
+        }
+        format(child, state, settings, output);
+    }
+    match state.mode {
+        Mode::Code => {}
+        _ => output.set_whitespace(Whitespace::LineBreak, Priority::Normal),
+    }
+    output.end_node(node);
+}
+
+/// Last item inside the parentheses.
+fn last_item(node: &SyntaxNode) -> Option<&SyntaxNode> {
+    node.children()
+        .take_while(|child| child.kind() != SyntaxKind::RightParen)
+        .filter(|child| {
+            !matches!(
+                child.kind(),
+                SyntaxKind::LeftParen
+                    | SyntaxKind::Comma
+                    | SyntaxKind::Space
+                    | SyntaxKind::LineComment
+                    | SyntaxKind::BlockComment
+            )
+        })
+        .last()
+}
+
+fn contains_linebreak(node: &SyntaxNode) -> bool {
+    node.text().contains('\n') || node.children().any(contains_linebreak)
+}
+
+pub fn format_closure(
+    node: &SyntaxNode,
+    state: State,
+    settings: &Settings,
+    output: &mut Output<impl OutputTarget>,
+) {
+    // named closures like `let f(x) = x` always need their parentheses
+    let arrow = node
+        .children()
+        .any(|child| child.kind() == SyntaxKind::Arrow);
+    for child in node.children() {
+        match child.kind() {
+            SyntaxKind::Params if arrow => format_closure_params(child, state, settings, output),
+            _ => format(child, state, settings, output),
+        }
+    }
+}
+
+fn format_closure_params(
+    node: &SyntaxNode,
+    state: State,
+    settings: &Settings,
+    output: &mut Output<impl OutputTarget>,
+) {
+    let parenthesized = node
+        .children()
+        .any(|child| child.kind() == SyntaxKind::LeftParen);
+    let mut items = node.children().filter(|child| {
+        !matches!(
+            child.kind(),
+            SyntaxKind::LeftParen | SyntaxKind::RightParen | SyntaxKind::Comma | SyntaxKind::Space
+        )
+    });
+    // only plain names can be written without parentheses
+    let single = match (items.next(), items.next()) {
+        (Some(item), None) if matches!(item.kind(), SyntaxKind::Ident | SyntaxKind::Underscore) => {
+            Some(item)
+        }
+        _ => None,
+    };
+
+    match (&settings.closure.single_parameter_parentheses, single) {
+        (ParameterParentheses::Always, Some(item)) if !parenthesized => {
+            output.raw_text("(", &state, settings);
+            format(item, state, settings, output);
+            output.set_whitespace(Whitespace::None, Priority::High);
+            output.raw_text(")", &state, settings);
+        }
+        (ParameterParentheses::Never, Some(item)) if parenthesized => {
+            format(item, state, settings, output)
+        }
+        _ => format_items(node, state, settings, output),
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 enum CellSize {
     FullLine,
@@ -546,10 +1320,32 @@ pub fn format_import(
     settings: &Settings,
     output: &mut Output<impl OutputTarget>,
 ) {
+    let is_comment = |child: &SyntaxNode| {
+        matches!(
+            child.kind(),
+            SyntaxKind::LineComment | SyntaxKind::BlockComment
+        )
+    };
+    let items = node
+        .children()
+        .find(|child| child.kind() == SyntaxKind::ImportItems)
+        .filter(|items| !node.children().any(is_comment) && !items.children().any(is_comment));
+
+    let mut after_colon = false;
     for child in node.children() {
         match child.kind() {
             SyntaxKind::Colon => {
-                format_optional_padding(child, state, settings, output, &settings.import_statement)
+                format_optional_padding(child, state, settings, output, &settings.import_statement);
+                after_colon = true;
+            }
+            // the parentheses are written with the items
+            SyntaxKind::LeftParen | SyntaxKind::RightParen | SyntaxKind::Space
+                if after_colon && items.is_some() => {}
+            SyntaxKind::ImportItems if items.is_some() => {
+                let parenthesized = node
+                    .children()
+                    .any(|child| child.kind() == SyntaxKind::LeftParen);
+                format_import_items(child, parenthesized, state, settings, output);
             }
             _ => format(child, state, settings, output),
         }
@@ -559,3 +1355,104 @@ pub fn format_import(
         _ => output.set_whitespace(Whitespace::LineBreak, Priority::Normal),
     }
 }
+
+fn format_import_items(
+    node: &SyntaxNode,
+    parenthesized: bool,
+    state: State,
+    settings: &Settings,
+    output: &mut Output<impl OutputTarget>,
+) {
+    let mut items = node
+        .children()
+        .filter(|child| !matches!(child.kind(), SyntaxKind::Comma | SyntaxKind::Space))
+        .collect::<Vec<_>>();
+    if settings.imports.sort_items {
+        items.sort_by_key(|item| import_item_name(item));
+    }
+    if settings.imports.deduplicate_items {
+        let mut seen = HashSet::new();
+        items.retain(|item| seen.insert((*item).clone().into_text()));
+    }
+
+    let fixpoint = output.create_fixpoint();
+    format_import_item_list(&items, parenthesized, false, state, settings, output);
+    let (_, column) = output.position();
+    output.set_fixpoint(fixpoint);
+
+    let max_width = settings.max_width(settings.imports.max_width);
+    let wrap = max_width != 0 && column > max_width;
+    format_import_item_list(&items, parenthesized || wrap, wrap, state, settings, output);
+}
+
+fn format_import_item_list(
+    items: &[&SyntaxNode],
+    parenthesized: bool,
+    wrap: bool,
+    mut state: State,
+    settings: &Settings,
+    output: &mut Output<impl OutputTarget>,
+) {
+    if parenthesized {
+        output.raw_text("(", &state, settings);
+        output.set_whitespace(Whitespace::None, Priority::High);
+    }
+    if wrap {
+        state.indent();
+    }
+    for (index, item) in items.iter().enumerate() {
+        if wrap {
+            output.set_whitespace(Whitespace::LineBreak, Priority::High);
+        }
+        format(item, state, settings, output);
+        if wrap || index + 1 < items.len() {
+            let before = match settings.comma.space_before {
+                true => Whitespace::Space,
+                false => Whitespace::None,
+            };
+            output.set_whitespace(before, Priority::High);
+            output.raw_text(",", &state, settings);
+            if settings.comma.space_after {
+                output.set_whitespace(Whitespace::Space, Priority::Normal);
+            }
+        }
+    }
+    if wrap {
+        state.dedent();
+        output.set_whitespace(Whitespace::LineBreak, Priority::High);
+    }
+    if parenthesized {
+        if !wrap {
+            output.set_whitespace(Whitespace::None, Priority::High);
+        }
+        output.raw_text(")", &state, settings);
+    }
+}
+
+/// Name of the imported item, renamed items use their original name.
+fn import_item_name(item: &SyntaxNode) -> String {
+    let original = match item.kind() {
+        SyntaxKind::RenamedImportItem => item
+            .children()
+            .find(|child| child.kind() != SyntaxKind::Space)
+            .unwrap_or(item),
+        _ => item,
+    };
+    original.clone().into_text().to_string()
+}
+
+/// Source of an import statement, packages are sorted before files and other modules.
+pub fn import_source_key(node: &SyntaxNode) -> (usize, String) {
+    let source = node
+        .children()
+        .find(|child| !matches!(child.kind(), SyntaxKind::Import | SyntaxKind::Space));
+    let Some(source) = source else {
+        return (3, String::new());
+    };
+    let text = source.clone().into_text().to_string();
+    match source.kind() {
+        SyntaxKind::Str if text.starts_with("\"@") => (0, text),
+        SyntaxKind::Str => (1, text),
+        _ => (2, text),
+    }
+}
@@ -0,0 +1,223 @@
+use typst_syntax::is_id_continue;
+
+use super::*;
+
+/// String literal with normalised escapes, the evaluated string stays the same.
+pub fn format_str(
+    node: &SyntaxNode,
+    state: State,
+    settings: &Settings,
+    output: &mut Output<impl OutputTarget>,
+) {
+    let strings = &settings.strings;
+    let content = node
+        .text()
+        .strip_prefix('"')
+        .and_then(|text| text.strip_suffix('"'));
+    let Some(content) = content else {
+        return output.verbatim_text(node.text(), &state, settings);
+    };
+    if !strings.normalize_escapes && matches!(strings.unicode, UnicodeStyle::Keep) {
+        return output.verbatim_text(node.text(), &state, settings);
+    }
+
+    let mut text = String::from('"');
+    let mut rest = content;
+    while let Some(c) = rest.chars().next() {
+        if c != '\\' {
+            match strings.unicode {
+                UnicodeStyle::Escaped if !c.is_ascii() => text.push_str(&unicode_escape(c)),
+                _ => text.push(c),
+            }
+            rest = &rest[c.len_utf8()..];
+            continue;
+        }
+
+        // unknown escapes keep the backslash and the character
+        let escape = match rest[1..].chars().next() {
+            Some('u') => match parse_unicode_escape(rest) {
+                Some((c, length)) => {
+                    text.push_str(&escaped_str_char(c, &rest[..length], strings));
+                    rest = &rest[length..];
+                    continue;
+                }
+                None => "\\u",
+            },
+            Some(c) => &rest[..1 + c.len_utf8()],
+            None => "\\",
+        };
+        text.push_str(escape);
+        rest = &rest[escape.len()..];
+    }
+    text.push('"');
+    output.verbatim_text(&text, &state, settings);
+}
+
+/// Escape in markup or math, only replaced by an escape or character with the same meaning.
+pub fn format_escape(
+    node: &SyntaxNode,
+    state: State,
+    settings: &Settings,
+    output: &mut Output<impl OutputTarget>,
+) {
+    let strings = &settings.strings;
+    let text = node.text();
+    let c = match parse_unicode_escape(text) {
+        Some((c, length)) if length == text.len() => c,
+        _ => match text.strip_prefix('\\').map(|rest| rest.chars()) {
+            Some(mut chars) if matches!(strings.unicode, UnicodeStyle::Escaped) => {
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) if !c.is_ascii() => {
+                        return output.raw_text(&unicode_escape(c), &state, settings);
+                    }
+                    _ => return format_default(node, state, settings, output),
+                }
+            }
+            _ => return format_default(node, state, settings, output),
+        },
+    };
+
+    // letters would join with neighbouring identifiers
+    let text = match strings.unicode {
+        UnicodeStyle::Literal if !c.is_ascii() && is_printable(c) && !is_id_continue(c) => {
+            c.to_string()
+        }
+        _ if !strings.normalize_escapes => text.into(),
+        _ if c.is_ascii_punctuation() => format!("\\{c}"),
+        _ => unicode_escape(c),
+    };
+    output.raw_text(&text, &state, settings);
+}
+
+/// Replacement for the `\u{...}` escape `original` of `c` inside a string literal.
+fn escaped_str_char(c: char, original: &str, strings: &StringsSettings) -> String {
+    match c {
+        _ if matches!(strings.unicode, UnicodeStyle::Literal)
+            && !c.is_ascii()
+            && is_printable(c) =>
+        {
+            c.to_string()
+        }
+        _ if !strings.normalize_escapes => original.into(),
+        '\\' => "\\\\".into(),
+        '"' => "\\\"".into(),
+        '\n' => "\\n".into(),
+        '\r' => "\\r".into(),
+        '\t' => "\\t".into(),
+        ' '..='~' => c.to_string(),
+        _ => unicode_escape(c),
+    }
+}
+
+/// Character and length of the `\u{...}` escape at the start of `text`.
+fn parse_unicode_escape(text: &str) -> Option<(char, usize)> {
+    let hex = text.strip_prefix("\\u{")?;
+    let end = hex.find('}')?;
+    let hex = &hex[..end];
+    if hex.is_empty() || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let c = u32::from_str_radix(hex, 16).ok().and_then(char::from_u32)?;
+    Some((c, "\\u{".len() + end + 1))
+}
+
+/// Canonical escape with lowercase digits and without leading zeros.
+fn unicode_escape(c: char) -> String {
+    format!("\\u{{{:x}}}", u32::from(c))
+}
+
+/// Visible characters which are safe to write without an escape.
+fn is_printable(c: char) -> bool {
+    !c.is_control()
+        && !c.is_whitespace()
+        && !matches!(
+            c,
+            '\u{ad}'
+                | '\u{300}'..='\u{36f}'
+                | '\u{61c}'
+                | '\u{115f}'..='\u{1160}'
+                | '\u{17b4}'..='\u{17b5}'
+                | '\u{180b}'..='\u{180f}'
+                | '\u{1ab0}'..='\u{1aff}'
+                | '\u{1dc0}'..='\u{1dff}'
+                | '\u{200b}'..='\u{200f}'
+                | '\u{202a}'..='\u{202e}'
+                | '\u{2060}'..='\u{206f}'
+                | '\u{20d0}'..='\u{20ff}'
+                | '\u{3164}'
+                | '\u{e000}'..='\u{f8ff}'
+                | '\u{fe00}'..='\u{fe0f}'
+                | '\u{fe20}'..='\u{fe2f}'
+                | '\u{feff}'
+                | '\u{ffa0}'
+                | '\u{fff0}'..='\u{fffb}'
+                | '\u{e0000}'..='\u{e0fff}'
+                | '\u{f0000}'..
+        )
+}
+
+/// Integer, float or numeric with normalised digits, the value stays the same.
+pub fn format_number(
+    node: &SyntaxNode,
+    state: State,
+    settings: &Settings,
+    output: &mut Output<impl OutputTarget>,
+) {
+    let numbers = &settings.numbers;
+    let text = node.text();
+    if let Some(digits) = text.strip_prefix("0x") {
+        return match numbers.lowercase {
+            true => output.raw_text(&format!("0x{}", digits.to_lowercase()), &state, settings),
+            false => format_default(node, state, settings, output),
+        };
+    }
+    if text.starts_with("0b") || text.starts_with("0o") {
+        return format_default(node, state, settings, output);
+    }
+
+    let unit_start = text
+        .trim_end_matches(|c: char| c.is_ascii_alphabetic() || c == '%')
+        .len();
+    let (number, unit) = text.split_at(unit_start);
+    let (mantissa, exponent) = match number.find(['e', 'E']) {
+        Some(index) => number.split_at(index),
+        None => (number, ""),
+    };
+    let (mut integer, fraction) = match mantissa.split_once('.') {
+        Some((integer, fraction)) => (integer, Some(fraction)),
+        None => (mantissa, None),
+    };
+
+    if numbers.leading_zero && integer.is_empty() {
+        integer = "0";
+    }
+    let fraction = match fraction {
+        Some(fraction) if numbers.remove_trailing_zeros => {
+            match fraction.trim_end_matches('0') {
+                // a float without exponent needs a fraction to stay a float
+                "" if node.kind() == SyntaxKind::Float && exponent.is_empty() => Some("0"),
+                "" => {
+                    if integer.is_empty() {
+                        integer = "0";
+                    }
+                    None
+                }
+                fraction => Some(fraction),
+            }
+        }
+        fraction => fraction,
+    };
+    let exponent = match numbers.lowercase {
+        true => exponent.replace('E', "e"),
+        false => exponent.into(),
+    };
+
+    let mut number = String::from(integer);
+    if let Some(fraction) = fraction {
+        number.push('.');
+        number.push_str(fraction);
+    }
+    number.push_str(&exponent);
+    number.push_str(unit);
+    output.raw_text(&number, &state, settings);
+}
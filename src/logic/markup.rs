@@ -6,8 +6,11 @@ pub fn format_markup(
     settings: &Settings,
     output: &mut Output<impl OutputTarget>,
 ) {
+    let children = node.children().collect::<Vec<_>>();
+    let padding = let_binding_padding(&children, state, settings, output);
     let mut disabled = false;
-    for child in node.children() {
+    let mut index = 0;
+    while let Some(&child) = children.get(index) {
         if (child.kind() == SyntaxKind::LineComment || child.kind() == SyntaxKind::BlockComment)
             && child.text().contains("prettypst")
         {
@@ -19,9 +22,67 @@ pub fn format_markup(
         }
         if disabled {
             skip_formatting(child, state, settings, output);
+        } else if settings.imports.group_statements
+            && let Some(length) = import_group(&children[index..])
+        {
+            format_import_group(&children[index..index + length], state, settings, output);
+            index += length;
+            continue;
+        } else if padding[index] > 0 {
+            format_aligned_let_binding(child, state, settings, output, padding[index]);
         } else {
             format(child, state, settings, output);
         }
+        index += 1;
+    }
+}
+
+/// Number of children covered by at least two `#import` lines without blank lines between them.
+fn import_group(children: &[&SyntaxNode]) -> Option<usize> {
+    let is_import = |index: usize| {
+        children.get(index).map(|child| child.kind()) == Some(SyntaxKind::Hash)
+            && children.get(index + 1).map(|child| child.kind()) == Some(SyntaxKind::ModuleImport)
+    };
+    let mut length = 0;
+    let mut imports = 0;
+    while is_import(length) {
+        length += 2;
+        imports += 1;
+        let single_linebreak = children.get(length).is_some_and(|child| {
+            child.kind() == SyntaxKind::Space && child.text().matches('\n').count() == 1
+        });
+        if !single_linebreak || !is_import(length + 1) {
+            break;
+        }
+        length += 1;
+    }
+    (imports >= 2).then_some(length)
+}
+
+/// Format the import lines sorted by their source.
+fn format_import_group(
+    children: &[&SyntaxNode],
+    state: State,
+    settings: &Settings,
+    output: &mut Output<impl OutputTarget>,
+) {
+    let mut imports = children
+        .windows(2)
+        .filter(|pair| pair[1].kind() == SyntaxKind::ModuleImport)
+        .map(|pair| (pair[0], pair[1]))
+        .collect::<Vec<_>>();
+    imports.sort_by_key(|(_, import)| import_source_key(import));
+
+    // the group is one unit for the selection, its nodes are reordered
+    for child in children {
+        output.begin_node(child);
+    }
+    for (hash, import) in imports {
+        format_kind(hash, state, settings, output);
+        format_kind(import, state, settings, output);
+    }
+    for child in children {
+        output.end_node(child);
     }
 }
 
@@ -1,8 +1,10 @@
 mod code;
+mod literal;
 mod markup;
 mod math;
 
 use code::*;
+use literal::*;
 use markup::*;
 use math::*;
 use typst_syntax::{SyntaxKind, SyntaxNode};
@@ -18,6 +20,17 @@ pub fn format(
     state: State,
     settings: &Settings,
     output: &mut Output<impl OutputTarget>,
+) {
+    output.begin_node(node);
+    format_kind(node, state, settings, output);
+    output.end_node(node);
+}
+
+fn format_kind(
+    node: &SyntaxNode,
+    state: State,
+    settings: &Settings,
+    output: &mut Output<impl OutputTarget>,
 ) {
     if node.erroneous()
         && node
@@ -35,7 +48,7 @@ pub fn format(
         SyntaxKind::Space => format_space(node, state, settings, output),
         SyntaxKind::Linebreak => format_and_new_line(node, state, settings, output),
         SyntaxKind::Parbreak => output.set_whitespace(Whitespace::LineBreaks(2), Priority::High),
-        SyntaxKind::Escape => format_default(node, state, settings, output),
+        SyntaxKind::Escape => format_escape(node, state, settings, output),
         SyntaxKind::Shorthand => format_default(node, state, settings, output),
         SyntaxKind::SmartQuote => format_default(node, state, settings, output),
         SyntaxKind::Strong => format_enclosed(node, state, settings, output),
@@ -124,34 +137,34 @@ pub fn format(
         SyntaxKind::As => output.raw(node, &state, settings),
         SyntaxKind::Context => output.raw(node, &state, settings),
 
-        SyntaxKind::Code => format_default(node, state, settings, output),
+        SyntaxKind::Code => format_code(node, state, settings, output, false),
         SyntaxKind::Ident => format_default(node, state, settings, output),
         SyntaxKind::Bool => format_default(node, state, settings, output),
-        SyntaxKind::Int => format_default(node, state, settings, output),
-        SyntaxKind::Float => format_default(node, state, settings, output),
-        SyntaxKind::Numeric => format_default(node, state, settings, output),
-        SyntaxKind::Str => format_default(node, state, settings, output),
+        SyntaxKind::Int => format_number(node, state, settings, output),
+        SyntaxKind::Float => format_number(node, state, settings, output),
+        SyntaxKind::Numeric => format_number(node, state, settings, output),
+        SyntaxKind::Str => format_str(node, state, settings, output),
         SyntaxKind::CodeBlock => format_code_block(node, state, settings, output),
         SyntaxKind::ContentBlock => format_content_block(node, state, settings, output),
-        SyntaxKind::Parenthesized => format_default(node, state, settings, output),
+        SyntaxKind::Parenthesized => format_parenthesized(node, state, settings, output),
         SyntaxKind::Array => format_items(node, state, settings, output),
         SyntaxKind::Dict => format_items(node, state, settings, output),
         SyntaxKind::Named => format_named_argument(node, state, settings, output),
         SyntaxKind::Keyed => format_keyed(node, state, settings, output),
         SyntaxKind::Unary => format_unary(node, state, settings, output),
-        SyntaxKind::Binary => format_default(node, state, settings, output),
-        SyntaxKind::FieldAccess => format_default(node, state, settings, output),
+        SyntaxKind::Binary => format_binary(node, state, settings, output),
+        SyntaxKind::FieldAccess => format_field_access(node, state, settings, output),
         SyntaxKind::FuncCall => format_func_call(node, state, settings, output),
         SyntaxKind::Args => format_items(node, state, settings, output),
         SyntaxKind::Spread => format_default(node, state, settings, output),
-        SyntaxKind::Closure => format_default(node, state, settings, output),
+        SyntaxKind::Closure => format_closure(node, state, settings, output),
         SyntaxKind::Params => format_items(node, state, settings, output),
         SyntaxKind::LetBinding => format_code_statement(node, state, settings, output),
         SyntaxKind::SetRule => format_code_statement(node, state, settings, output),
         SyntaxKind::ShowRule => format_code_statement(node, state, settings, output),
-        SyntaxKind::Conditional => format_default(node, state, settings, output),
-        SyntaxKind::WhileLoop => format_default(node, state, settings, output),
-        SyntaxKind::ForLoop => format_default(node, state, settings, output),
+        SyntaxKind::Conditional => format_control_flow(node, state, settings, output),
+        SyntaxKind::WhileLoop => format_control_flow(node, state, settings, output),
+        SyntaxKind::ForLoop => format_control_flow(node, state, settings, output),
         SyntaxKind::ModuleImport => format_import(node, state, settings, output),
         SyntaxKind::ImportItems => format_default(node, state, settings, output),
         SyntaxKind::ImportItemPath => format_default(node, state, settings, output),
@@ -0,0 +1,212 @@
+use std::{
+    collections::HashMap,
+    io::{BufRead, Write},
+    path::PathBuf,
+};
+
+use serde_json::{Value, json};
+
+use crate::{Command, FormatError, LineRange, format_lines, format_str, resolve_settings};
+
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+/// Language server which formats whole documents or line ranges, until the client exits.
+///
+/// Only full text synchronization is supported, the settings are resolved for the path of every
+/// document like for files on the command line.
+pub fn run(command: &Command) -> Result<(), FormatError> {
+    let mut input = std::io::stdin().lock();
+    let mut output = std::io::stdout().lock();
+    let mut documents = HashMap::<String, String>::new();
+
+    while let Some(message) = read_message(&mut input)? {
+        let Ok(message) = serde_json::from_str::<Value>(&message) else {
+            continue;
+        };
+        let id = message.get("id").cloned();
+        let method = message.get("method").and_then(Value::as_str).unwrap_or("");
+        let params = message.get("params").cloned().unwrap_or(Value::Null);
+
+        let result = match method {
+            "initialize" => Ok(json!({
+                "capabilities": {
+                    "textDocumentSync": 1,
+                    "documentFormattingProvider": true,
+                    "documentRangeFormattingProvider": true,
+                },
+                "serverInfo": { "name": "prettypst", "version": env!("CARGO_PKG_VERSION") },
+            })),
+            "textDocument/didOpen" => {
+                if let (Some(uri), Some(text)) = (
+                    params["textDocument"]["uri"].as_str(),
+                    params["textDocument"]["text"].as_str(),
+                ) {
+                    documents.insert(uri.into(), text.into());
+                }
+                continue;
+            }
+            "textDocument/didChange" => {
+                let uri = params["textDocument"]["uri"].as_str();
+                let text = params["contentChanges"]
+                    .as_array()
+                    .and_then(|changes| changes.last())
+                    .and_then(|change| change["text"].as_str());
+                if let (Some(uri), Some(text)) = (uri, text) {
+                    documents.insert(uri.into(), text.into());
+                }
+                continue;
+            }
+            "textDocument/didClose" => {
+                if let Some(uri) = params["textDocument"]["uri"].as_str() {
+                    documents.remove(uri);
+                }
+                continue;
+            }
+            "textDocument/formatting" => format_document(command, &documents, &params, None),
+            "textDocument/rangeFormatting" => {
+                let (start, end) = (&params["range"]["start"], &params["range"]["end"]);
+                let start = start["line"].as_u64().unwrap_or(0) as usize + 1;
+                let mut end = end["line"].as_u64().unwrap_or(0) as usize + 1;
+                // a range ending at the start of a line does not contain it
+                if params["range"]["end"]["character"].as_u64() == Some(0) && end > start {
+                    end -= 1;
+                }
+                let lines = LineRange { start, end };
+                format_document(command, &documents, &params, Some(lines))
+            }
+            "shutdown" => Ok(Value::Null),
+            "exit" => return Ok(()),
+            _ => Err((METHOD_NOT_FOUND, format!("unknown method '{method}'"))),
+        };
+
+        // notifications have no id and get no response
+        let Some(id) = id else {
+            continue;
+        };
+        let response = match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err((code, message)) => json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "error": { "code": code, "message": message },
+            }),
+        };
+        write_message(&mut output, &response.to_string())?;
+    }
+    Ok(())
+}
+
+/// Edit which replaces the whole document, `null` if it is already formatted.
+fn format_document(
+    command: &Command,
+    documents: &HashMap<String, String>,
+    params: &Value,
+    lines: Option<LineRange>,
+) -> Result<Value, (i64, String)> {
+    let uri = params["textDocument"]["uri"].as_str().unwrap_or("");
+    let text = documents
+        .get(uri)
+        .ok_or_else(|| (INVALID_PARAMS, format!("unknown document '{uri}'")))?;
+    let document = document_path(uri);
+    let (settings, _) =
+        resolve_settings(command, &document).map_err(|err| (INVALID_PARAMS, err.to_string()))?;
+
+    let output = match lines {
+        Some(lines) => format_lines(text, &settings, &[lines]).0,
+        None => {
+            let mut output = Vec::new();
+            format_str(text, &settings, &mut output);
+            String::from_utf8_lossy(&output).into_owned()
+        }
+    };
+    if &output == text {
+        return Ok(Value::Null);
+    }
+    let start = json!({ "line": 0, "character": 0 });
+    let range = json!({ "start": start, "end": end_position(text) });
+    Ok(json!([{ "range": range, "newText": output }]))
+}
+
+/// Position after the last character, with the column in UTF-16 code units.
+fn end_position(text: &str) -> Value {
+    let line = text.matches('\n').count();
+    let last = text.rsplit('\n').next().unwrap_or("");
+    json!({ "line": line, "character": last.encode_utf16().count() })
+}
+
+/// Path of a 'file' URI, other URIs are treated as a document in the working directory.
+fn document_path(uri: &str) -> PathBuf {
+    let Some(path) = uri.strip_prefix("file://") else {
+        return PathBuf::from(crate::STDIN_DOCUMENT);
+    };
+    let mut bytes = Vec::with_capacity(path.len());
+    let mut rest = path.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        let decoded = match (byte, tail) {
+            (b'%', [high, low, ..]) => std::str::from_utf8(&[*high, *low])
+                .ok()
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok()),
+            _ => None,
+        };
+        match decoded {
+            Some(decoded) => {
+                bytes.push(decoded);
+                rest = &tail[2..];
+            }
+            None => {
+                bytes.push(byte);
+                rest = tail;
+            }
+        }
+    }
+    let path = String::from_utf8_lossy(&bytes).into_owned();
+    // 'file:///C:/...' on windows
+    let path = match path.strip_prefix('/') {
+        Some(windows) if windows.get(1..2) == Some(":") => windows.to_owned(),
+        _ => path,
+    };
+    std::path::absolute(&path).unwrap_or_else(|_| PathBuf::from(path))
+}
+
+fn read_message(input: &mut impl BufRead) -> Result<Option<String>, FormatError> {
+    let mut length = None;
+    loop {
+        let mut line = String::new();
+        if input
+            .read_line(&mut line)
+            .map_err(FormatError::FailedToReadStdIn)?
+            == 0
+        {
+            return Ok(None);
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':')
+            && name.eq_ignore_ascii_case("content-length")
+        {
+            length = value.trim().parse::<usize>().ok();
+        }
+    }
+    let Some(length) = length else {
+        return Ok(Some(String::new()));
+    };
+    let mut content = vec![0; length];
+    input
+        .read_exact(&mut content)
+        .map_err(FormatError::FailedToReadStdIn)?;
+    Ok(Some(String::from_utf8_lossy(&content).into_owned()))
+}
+
+fn write_message(output: &mut impl Write, content: &str) -> Result<(), FormatError> {
+    write!(
+        output,
+        "Content-Length: {}\r\n\r\n{}",
+        content.len(),
+        content
+    )
+    .and_then(|()| output.flush())
+    .map_err(FormatError::FailedToWriteStdOut)
+}
@@ -8,7 +8,7 @@ fn main() {
         Ok(()) => {}
         Err(err) => {
             eprintln!("{err}");
-            std::process::exit(1);
+            std::process::exit(err.exit_code() as i32);
         }
     }
 }
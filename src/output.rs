@@ -1,7 +1,9 @@
+use std::borrow::Cow;
+
 use typst_syntax::SyntaxNode;
 
-use super::settings::Settings;
-use crate::state::State;
+use super::settings::{LineEnding, Settings};
+use crate::{selection::Selection, state::State};
 
 #[derive(Clone, Copy)]
 pub enum Whitespace {
@@ -31,6 +33,16 @@ impl<T: std::io::Write> OutputTarget for T {
     }
 }
 
+fn with_line_ending(text: &str, line_ending: LineEnding) -> Cow<'_, str> {
+    match line_ending {
+        LineEnding::Lf if text.contains('\r') => Cow::Owned(text.replace("\r\n", "\n")),
+        LineEnding::Crlf if text.contains('\n') => {
+            Cow::Owned(text.replace("\r\n", "\n").replace('\n', "\r\n"))
+        }
+        _ => Cow::Borrowed(text),
+    }
+}
+
 pub struct Output<'a, Target: OutputTarget> {
     target: &'a mut Target,
     whitespace: Whitespace,
@@ -39,6 +51,12 @@ pub struct Output<'a, Target: OutputTarget> {
     enabled: bool,
     line: usize,
     column: usize,
+    /// Last emitted character
+    last: Option<char>,
+
+    /// Bytes emitted to the target
+    written: usize,
+    selection: Option<&'a mut Selection>,
 }
 
 pub struct Fixpoint {
@@ -48,6 +66,7 @@ pub struct Fixpoint {
     enabled: bool,
     line: usize,
     column: usize,
+    last: Option<char>,
 }
 
 impl<'a, Target: OutputTarget> Output<'a, Target> {
@@ -60,6 +79,32 @@ impl<'a, Target: OutputTarget> Output<'a, Target> {
             enabled: true,
             line: 0,
             column: 0,
+            last: None,
+
+            written: 0,
+            selection: None,
+        }
+    }
+
+    /// Record the output positions of the selected units.
+    pub fn with_selection(mut self, selection: &'a mut Selection) -> Self {
+        self.selection = Some(selection);
+        self
+    }
+
+    pub fn begin_node(&mut self, node: &SyntaxNode) {
+        if let Some(selection) = &mut self.selection
+            && self.enabled
+        {
+            selection.begin(node);
+        }
+    }
+
+    pub fn end_node(&mut self, node: &SyntaxNode) {
+        if let Some(selection) = &mut self.selection
+            && self.enabled
+        {
+            selection.end(node, self.written);
         }
     }
 
@@ -71,6 +116,7 @@ impl<'a, Target: OutputTarget> Output<'a, Target> {
             enabled: self.enabled,
             line: self.line,
             column: self.column,
+            last: self.last,
         };
         self.enabled = false;
         fixpoint
@@ -91,12 +137,17 @@ impl<'a, Target: OutputTarget> Output<'a, Target> {
         self.enabled = fixpoint.enabled;
         self.line = fixpoint.line;
         self.column = fixpoint.column;
+        self.last = fixpoint.last;
     }
 
     pub fn position(&self) -> (usize, usize) {
         (self.line, self.column)
     }
 
+    pub fn last_char(&self) -> Option<char> {
+        self.last
+    }
+
     fn emit_indentation(&mut self, state: &State, settings: &Settings) {
         if state.indentation + state.extra_indentation == 0 {
             return;
@@ -121,6 +172,11 @@ impl<'a, Target: OutputTarget> Output<'a, Target> {
     }
 
     fn emit(&mut self, text: &str, settings: &Settings) {
+        self.emit_with(text, false, settings);
+    }
+
+    /// Verbatim text keeps its line endings, because they are part of its value.
+    fn emit_with(&mut self, text: &str, verbatim: bool, settings: &Settings) {
         for symbol in text.chars() {
             match symbol {
                 '\t' => {
@@ -133,9 +189,15 @@ impl<'a, Target: OutputTarget> Output<'a, Target> {
                 }
                 _ => self.column += 1,
             }
+            self.last = Some(symbol);
         }
         if self.enabled {
-            self.target.emit(text, settings);
+            let text = match verbatim {
+                true => Cow::Borrowed(text),
+                false => with_line_ending(text, settings.line_ending),
+            };
+            self.written += text.len();
+            self.target.emit(&text, settings);
         }
     }
 
@@ -160,19 +222,29 @@ impl<'a, Target: OutputTarget> Output<'a, Target> {
     }
 
     pub fn raw(&mut self, node: &SyntaxNode, state: &State, settings: &Settings) {
-        if node.text().is_empty() {
-            return;
-        }
-        self.emit_whitespace(state, settings);
-        self.emit(node.text(), settings);
+        self.raw_text(node.text(), state, settings);
     }
 
     pub fn raw_text(&mut self, text: &str, state: &State, settings: &Settings) {
+        self.text(text, false, state, settings);
+    }
+
+    /// Text like string literals, whose line breaks are not changed to the line ending.
+    pub fn verbatim_text(&mut self, text: &str, state: &State, settings: &Settings) {
+        self.text(text, true, state, settings);
+    }
+
+    fn text(&mut self, text: &str, verbatim: bool, state: &State, settings: &Settings) {
         if text.is_empty() {
             return;
         }
         self.emit_whitespace(state, settings);
-        self.emit(text, settings);
+        if let Some(selection) = &mut self.selection
+            && self.enabled
+        {
+            selection.text(self.written);
+        }
+        self.emit_with(text, verbatim, settings);
     }
 
     pub fn set_whitespace(&mut self, whitespace: Whitespace, priority: Priority) {
@@ -0,0 +1,73 @@
+use std::{path::PathBuf, time::Duration};
+
+use clap::ValueEnum;
+use serde::Serialize;
+use similar::{DiffTag, TextDiff};
+
+use crate::diagnostics::ParseError;
+
+/// Increased for every incompatible change of the report format
+pub const REPORT_VERSION: u32 = 1;
+
+#[derive(Debug, Copy, Clone, ValueEnum)]
+pub enum ReportFormat {
+    /// One JSON object per line for every processed file
+    Json,
+}
+
+/// Lines of the input, starting at 1 and including `end`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct LineRange {
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FileReport {
+    pub version: u32,
+    pub path: String,
+    pub changed: bool,
+    pub changed_lines: Vec<LineRange>,
+    pub parse_errors: Vec<ParseError>,
+    pub configuration: Option<PathBuf>,
+    pub duration_ms: f64,
+}
+
+impl FileReport {
+    pub fn new(
+        path: String,
+        input: &str,
+        output: &str,
+        parse_errors: Vec<ParseError>,
+        configuration: Option<PathBuf>,
+        duration: Duration,
+    ) -> Self {
+        Self {
+            version: REPORT_VERSION,
+            path,
+            changed: input != output,
+            changed_lines: changed_lines(input, output),
+            parse_errors,
+            configuration,
+            duration_ms: duration.as_secs_f64() * 1000.0,
+        }
+    }
+}
+
+/// Lines of `input` which differ in `output`, insertions mark the following line.
+pub fn changed_lines(input: &str, output: &str) -> Vec<LineRange> {
+    let mut ranges = Vec::<LineRange>::new();
+    for operation in TextDiff::from_lines(input, output).ops() {
+        if operation.tag() == DiffTag::Equal {
+            continue;
+        }
+        let lines = operation.old_range();
+        let start = lines.start + 1;
+        let end = lines.end.max(start);
+        match ranges.last_mut() {
+            Some(last) if last.end + 1 >= start => last.end = last.end.max(end),
+            _ => ranges.push(LineRange { start, end }),
+        }
+    }
+    ranges
+}
@@ -0,0 +1,148 @@
+use std::collections::BTreeMap;
+
+use clap::ValueEnum;
+use serde_json::{Map, Value, json};
+
+use crate::{FormatError, settings::Settings, styles::Styles};
+
+pub trait Schema {
+    fn schema() -> Value;
+}
+
+impl Schema for usize {
+    fn schema() -> Value {
+        json!({ "type": "integer", "minimum": 0 })
+    }
+}
+
+impl Schema for bool {
+    fn schema() -> Value {
+        json!({ "type": "boolean" })
+    }
+}
+
+impl Schema for String {
+    fn schema() -> Value {
+        json!({ "type": "string" })
+    }
+}
+
+impl<T: Schema> Schema for BTreeMap<String, T> {
+    fn schema() -> Value {
+        json!({
+            "type": "object",
+            "additionalProperties": T::schema(),
+        })
+    }
+}
+
+/// JSON Schema for `prettypst.toml`, with the values of every style as defaults.
+pub fn settings_schema() -> Value {
+    let mut schema = Settings::schema();
+    for style in Styles::value_variants() {
+        let Ok(values) = serde_json::to_value(style.settings()) else {
+            continue;
+        };
+        add_defaults(&mut schema, &style.to_string(), &values);
+    }
+
+    let mut root = Map::new();
+    root.insert(
+        "$schema".into(),
+        "http://json-schema.org/draft-07/schema#".into(),
+    );
+    root.insert("title".into(), "prettypst.toml".into());
+    root.insert(
+        "description".into(),
+        "Formatting settings for prettypst".into(),
+    );
+    if let Value::Object(schema) = schema {
+        root.extend(schema);
+    }
+    Value::Object(root)
+}
+
+fn add_defaults(schema: &mut Value, style: &str, values: &Value) {
+    let Some(schema) = schema.as_object_mut() else {
+        return;
+    };
+    if let Some(Value::Object(properties)) = schema.get_mut("properties") {
+        for (name, property) in properties.iter_mut() {
+            if let Some(value) = values.get(name) {
+                add_defaults(property, style, value);
+            }
+        }
+        return;
+    }
+
+    if style == Styles::Default.to_string() {
+        schema.insert("default".into(), values.clone());
+    }
+    if let Value::Object(defaults) = schema
+        .entry("x-style-defaults")
+        .or_insert_with(|| Value::Object(Map::new()))
+    {
+        defaults.insert(style.into(), values.clone());
+    }
+}
+
+/// Description of a setting with its allowed values and the value of every style, every setting
+/// is listed without a name.
+pub fn explain(setting: Option<&str>) -> Result<String, FormatError> {
+    let schema = settings_schema();
+    let Some(setting) = setting else {
+        let mut names = Vec::new();
+        leaf_names(&schema, String::new(), &mut names);
+        return Ok(names.into_iter().map(|name| name + "\n").collect());
+    };
+
+    let property = find_setting(&schema, setting)
+        .ok_or_else(|| FormatError::UnknownSetting(setting.into()))?;
+
+    let mut text = format!("{setting}\n");
+    match (property.get("type"), property.get("enum")) {
+        (_, Some(Value::Array(variants))) => {
+            let variants = variants.iter().map(Value::to_string).collect::<Vec<_>>();
+            text.push_str(&format!("  values: {}\n", variants.join(" | ")));
+        }
+        (Some(Value::String(kind)), _) if kind == "object" => {
+            let mut names = Vec::new();
+            leaf_names(property, format!("{setting}."), &mut names);
+            for name in names {
+                text.push_str(&format!("  {name}\n"));
+            }
+            return Ok(text);
+        }
+        (Some(Value::String(kind)), _) => text.push_str(&format!("  type: {kind}\n")),
+        _ => {}
+    }
+    if let Some(Value::Object(defaults)) = property.get("x-style-defaults") {
+        for (style, value) in defaults {
+            text.push_str(&format!("  {style}: {value}\n"));
+        }
+    }
+    Ok(text)
+}
+
+/// Schema of the setting with the dotted name, entries of maps use the schema of their values.
+pub fn find_setting<'a>(schema: &'a Value, setting: &str) -> Option<&'a Value> {
+    setting
+        .split('.')
+        .try_fold(schema, |schema, name| match schema.get("properties") {
+            Some(properties) => properties.get(name),
+            None => schema
+                .get("additionalProperties")
+                .filter(|values| values.is_object()),
+        })
+}
+
+fn leaf_names(schema: &Value, prefix: String, names: &mut Vec<String>) {
+    match schema.get("properties") {
+        Some(Value::Object(properties)) => {
+            for (name, property) in properties {
+                leaf_names(property, format!("{prefix}{name}."), names);
+            }
+        }
+        _ => names.push(prefix.trim_end_matches('.').into()),
+    }
+}
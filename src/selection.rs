@@ -0,0 +1,207 @@
+use std::{collections::HashMap, ops::Range};
+
+use typst_syntax::{SyntaxKind, SyntaxNode};
+
+use crate::report::LineRange;
+
+/// Nodes which are formatted as a whole if one of their lines is selected.
+const UNIT_KINDS: &[SyntaxKind] = &[
+    SyntaxKind::Heading,
+    SyntaxKind::ListItem,
+    SyntaxKind::EnumItem,
+    SyntaxKind::TermItem,
+    SyntaxKind::Equation,
+    SyntaxKind::FuncCall,
+    SyntaxKind::CodeBlock,
+    SyntaxKind::ContentBlock,
+    SyntaxKind::LetBinding,
+    SyntaxKind::SetRule,
+    SyntaxKind::ShowRule,
+];
+
+/// Parts of the document to format, everything else is kept as it is.
+///
+/// The output positions of every unit are recorded while the whole document is formatted,
+/// units which are never formatted (disabled or erroneous regions) keep their source.
+#[derive(Debug, Default)]
+pub struct Selection {
+    /// Byte ranges of the units in the source, sorted and without overlap
+    units: Vec<Range<usize>>,
+    /// Byte ranges of the units in the output
+    formatted: Vec<Option<Range<usize>>>,
+    starts: HashMap<*const SyntaxNode, usize>,
+    ends: HashMap<*const SyntaxNode, usize>,
+    pending: Option<usize>,
+    current: Option<(usize, usize)>,
+}
+
+struct Unit<'a> {
+    range: Range<usize>,
+    first: &'a SyntaxNode,
+    last: &'a SyntaxNode,
+}
+
+impl Selection {
+    /// Select the smallest paragraph, embedded expression or unit node around each of the non-empty
+    /// `lines`.
+    pub fn new(root: &SyntaxNode, source: &str, lines: &[LineRange]) -> Self {
+        let mut candidates = Vec::new();
+        collect(root, 0, &mut candidates);
+
+        let mut chosen = line_ranges(source, lines)
+            .filter_map(|line| {
+                candidates
+                    .iter()
+                    .filter(|unit| unit.range.start <= line.start && line.end <= unit.range.end)
+                    .min_by_key(|unit| unit.range.len())
+            })
+            .collect::<Vec<_>>();
+        // outer units first, nested units are part of them
+        chosen.sort_by_key(|unit| (unit.range.start, usize::MAX - unit.range.end));
+
+        let mut selection = Self::default();
+        for unit in chosen {
+            if selection
+                .units
+                .last()
+                .is_some_and(|last| unit.range.start < last.end)
+            {
+                continue;
+            }
+            let index = selection.units.len();
+            selection.units.push(unit.range.clone());
+            selection.formatted.push(None);
+            selection.starts.insert(unit.first, index);
+            selection.ends.insert(unit.last, index);
+        }
+        selection
+    }
+
+    pub fn begin(&mut self, node: &SyntaxNode) {
+        if let Some(&index) = self.starts.get(&(node as *const _)) {
+            self.pending = Some(index);
+        }
+    }
+
+    /// Called before the first text of a node is emitted, after the whitespace in front of it.
+    pub fn text(&mut self, position: usize) {
+        if let Some(index) = self.pending.take() {
+            self.current = Some((index, position));
+        }
+    }
+
+    pub fn end(&mut self, node: &SyntaxNode, position: usize) {
+        let Some(&index) = self.ends.get(&(node as *const _)) else {
+            return;
+        };
+        if let Some((start_index, start)) = self.current.take()
+            && start_index == index
+        {
+            self.formatted[index] = Some(start..position);
+        }
+        self.pending = None;
+    }
+
+    /// Replace the selected units in `source` with their formatted version from `output`.
+    pub fn apply(&self, source: &str, output: &str) -> String {
+        let mut result = String::with_capacity(source.len());
+        let mut source_end = 0;
+        let mut output_end = 0;
+        for (unit, formatted) in self.units.iter().zip(self.formatted.iter()) {
+            result.push_str(&source[source_end..unit.start]);
+            match formatted {
+                Some(formatted) if formatted.start >= output_end => {
+                    result.push_str(&output[formatted.clone()]);
+                    output_end = formatted.end;
+                }
+                _ => result.push_str(&source[unit.clone()]),
+            }
+            source_end = unit.end;
+        }
+        result.push_str(&source[source_end..]);
+        result
+    }
+}
+
+fn collect<'a>(node: &'a SyntaxNode, offset: usize, units: &mut Vec<Unit<'a>>) {
+    if UNIT_KINDS.contains(&node.kind()) {
+        units.push(Unit {
+            range: offset..offset + node.len(),
+            first: node,
+            last: node,
+        });
+    }
+    if node.kind() == SyntaxKind::Markup {
+        collect_paragraphs(node, offset, units);
+    }
+    let mut offset = offset;
+    let mut children = node.children().peekable();
+    while let Some(child) = children.next() {
+        // the hash and the embedded expression are formatted together
+        if child.kind() == SyntaxKind::Hash
+            && let Some(&expression) = children.peek()
+        {
+            units.push(Unit {
+                range: offset..offset + child.len() + expression.len(),
+                first: child,
+                last: expression,
+            });
+        }
+        collect(child, offset, units);
+        offset += child.len();
+    }
+}
+
+/// Paragraphs are runs of markup between paragraph breaks, without the surrounding spaces.
+fn collect_paragraphs<'a>(node: &'a SyntaxNode, offset: usize, units: &mut Vec<Unit<'a>>) {
+    let mut paragraph: Option<Unit<'a>> = None;
+    let mut offset = offset;
+    for child in node.children() {
+        let range = offset..offset + child.len();
+        offset = range.end;
+        match child.kind() {
+            SyntaxKind::Parbreak => units.extend(paragraph.take()),
+            SyntaxKind::Space => {}
+            _ => match &mut paragraph {
+                Some(paragraph) => {
+                    paragraph.range.end = range.end;
+                    paragraph.last = child;
+                }
+                None => {
+                    paragraph = Some(Unit {
+                        range,
+                        first: child,
+                        last: child,
+                    })
+                }
+            },
+        }
+    }
+    units.extend(paragraph);
+}
+
+/// Byte ranges of the selected lines, without surrounding whitespace and empty lines.
+fn line_ranges<'a>(
+    source: &'a str,
+    lines: &'a [LineRange],
+) -> impl Iterator<Item = Range<usize>> + 'a {
+    source
+        .split_inclusive('\n')
+        .scan(0, |offset, line| {
+            let start = *offset;
+            *offset += line.len();
+            Some((start, line))
+        })
+        .enumerate()
+        .filter(|(index, _)| {
+            lines
+                .iter()
+                .any(|range| (range.start..=range.end).contains(&(index + 1)))
+        })
+        .filter_map(|(_, (start, line))| {
+            let trimmed = line.trim_start();
+            let start = start + line.len() - trimmed.len();
+            let end = start + trimmed.trim_end().len();
+            (start < end).then_some(start..end)
+        })
+}
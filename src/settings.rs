@@ -1,11 +1,15 @@
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{BTreeMap, HashSet},
     path::PathBuf,
 };
 
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value, json};
 
-use crate::FormatError;
+use crate::{
+    FormatError,
+    schema::{Schema, find_setting},
+};
 
 trait Overwrite {
     type Partial;
@@ -25,10 +29,34 @@ macro_rules! identity_overwrite {
     };
 }
 
+macro_rules! create_enum_and_schema {
+    ($($(#[$attribute:meta])* enum $name:ident { $($variant:ident,)* })*) => {
+        $(
+            #[derive(Deserialize, Serialize, Debug)]
+            #[serde(rename_all = "kebab-case")]
+            $(#[$attribute])*
+            pub enum $name {
+                $($variant,)*
+            }
+
+            identity_overwrite!($name);
+
+            impl Schema for $name {
+                fn schema() -> Value {
+                    json!({
+                        "type": "string",
+                        "enum": [$($name::$variant),*]
+                    })
+                }
+            }
+        )*
+    };
+}
+
 macro_rules! create_normal_and_partial {
     ($(struct $name:ident | $partial_name:ident {$(pub $member:ident: $member_type:ty,)*})*) => {
         $(
-            #[derive(Serialize, Debug)]
+            #[derive(Serialize, Deserialize, Debug)]
             #[serde(rename_all = "kebab-case")]
             pub struct $name {
                 $(
@@ -54,31 +82,74 @@ macro_rules! create_normal_and_partial {
                     )*
                 }
             }
+
+            impl Schema for $name {
+                fn schema() -> Value {
+                    let mut properties = Map::new();
+                    $(
+                        properties.insert(
+                            stringify!($member).replace('_', "-"),
+                            <$member_type as Schema>::schema(),
+                        );
+                    )*
+                    json!({
+                        "type": "object",
+                        "additionalProperties": false,
+                        "properties": properties
+                    })
+                }
+            }
         )*
     };
 }
 
-#[derive(Deserialize, Serialize, Debug)]
-#[serde(rename_all = "kebab-case")]
-pub enum LongBlockStyle {
-    Compact,
-    Separate,
-}
+identity_overwrite!(usize, bool);
 
-#[derive(Deserialize, Serialize, Debug)]
-#[serde(rename_all = "kebab-case")]
-pub enum AlignComma {
-    EndOfContent,
-    EndOfCell,
-}
+create_enum_and_schema!(
+    enum LongBlockStyle {
+        Compact,
+        Separate,
+    }
+
+    enum AlignComma {
+        EndOfContent,
+        EndOfCell,
+    }
+
+    enum OperatorPosition {
+        Leading,
+        Trailing,
+    }
+
+    enum ElsePosition {
+        SameLine,
+        NewLine,
+    }
+
+    enum ParameterParentheses {
+        Keep,
+        Always,
+        Never,
+    }
 
-identity_overwrite!(usize, bool, LongBlockStyle, AlignComma);
+    enum UnicodeStyle {
+        Keep,
+        Literal,
+        Escaped,
+    }
 
-impl<K: std::hash::Hash + std::cmp::Eq, T> Overwrite for HashMap<K, T> {
+    #[derive(Clone, Copy)]
+    enum LineEnding {
+        Lf,
+        Crlf,
+    }
+);
+
+impl<K: std::cmp::Ord, T> Overwrite for BTreeMap<K, T> {
     type Partial = Self;
 
     fn overwrite(&mut self, other: Self::Partial) {
-        *self = other;
+        self.extend(other);
     }
 }
 
@@ -95,6 +166,10 @@ create_normal_and_partial!(
         pub long_block_style: LongBlockStyle,
     }
 
+    struct ItemsSettings | PartialItemsSettings {
+        pub expand_trailing_comma: bool,
+    }
+
     struct HeadingSettings | PartialHeadingSettings {
         pub blank_lines_before: usize,
         pub blank_lines_after: usize,
@@ -121,31 +196,152 @@ create_normal_and_partial!(
         pub in_emphasis: bool,
     }
 
+    struct ChainSettings | PartialChainSettings {
+        pub max_width: usize,
+        pub min_calls: usize,
+    }
+
+    struct BinarySettings | PartialBinarySettings {
+        pub max_width: usize,
+        pub operator_position: OperatorPosition,
+    }
+
+    struct ControlFlowSettings | PartialControlFlowSettings {
+        pub else_position: ElsePosition,
+    }
+
+    struct ClosureSettings | PartialClosureSettings {
+        pub single_parameter_parentheses: ParameterParentheses,
+        pub hug_last_argument: bool,
+    }
+
+    struct ImportsSettings | PartialImportsSettings {
+        pub sort_items: bool,
+        pub deduplicate_items: bool,
+        pub group_statements: bool,
+        pub max_width: usize,
+    }
+
+    struct AlignmentSettings | PartialAlignmentSettings {
+        pub let_bindings: bool,
+        pub named_entries: bool,
+    }
+
+    struct StringsSettings | PartialStringsSettings {
+        pub normalize_escapes: bool,
+        pub unicode: UnicodeStyle,
+    }
+
+    struct NumbersSettings | PartialNumbersSettings {
+        pub leading_zero: bool,
+        pub remove_trailing_zeros: bool,
+        pub lowercase: bool,
+    }
+
+    struct ParenthesesSettings | PartialParenthesesSettings {
+        pub remove_redundant: bool,
+        pub clarify_mixed_logic: bool,
+    }
+
+    struct SemicolonsSettings | PartialSemicolonsSettings {
+        pub remove_trailing: bool,
+        pub split_statements: bool,
+    }
+
     struct Settings | PartialSettings {
         pub indentation: usize,
         pub separate_label: bool,
         pub final_newline: bool,
+        pub line_ending: LineEnding,
         pub preserve_newline: PreserveNewLine,
         pub automatic_newline: AutomaticNewLine,
         pub block: BlockSettings,
+        pub items: ItemsSettings,
+        pub chain: ChainSettings,
+        pub binary: BinarySettings,
+        pub control_flow: ControlFlowSettings,
+        pub closure: ClosureSettings,
         pub term: PaddingSettings,
         pub named_argument: PaddingSettings,
         pub dictionary_entry: PaddingSettings,
         pub import_statement: PaddingSettings,
+        pub imports: ImportsSettings,
         pub comma: PaddingSettings,
         pub columns: ColumnsSettings,
+        pub alignment: AlignmentSettings,
+        pub strings: StringsSettings,
+        pub numbers: NumbersSettings,
+        pub parentheses: ParenthesesSettings,
+        pub semicolons: SemicolonsSettings,
         pub heading: HeadingSettings,
 
-        pub columns_methods: HashMap<String, String>,
+        pub columns_methods: BTreeMap<String, String>,
     }
 );
 
 impl Settings {
+    /// Width of a single construct, zero uses `automatic-newline.max-width`.
+    pub fn max_width(&self, width: usize) -> usize {
+        match width {
+            0 => self.automatic_newline.max_width,
+            width => width,
+        }
+    }
+
     pub fn overwrite(&mut self, path: &PathBuf) -> Result<(), FormatError> {
         let data =
             std::fs::read_to_string(path).map_err(FormatError::FailedToReadConfigurationFile)?;
-        let partial = toml::from_str(&data)?;
+        self.overwrite_str(&data)?;
+        Ok(())
+    }
+
+    pub(crate) fn overwrite_str(&mut self, data: &str) -> Result<(), toml::de::Error> {
+        let partial = toml::from_str(data)?;
         <Self as Overwrite>::overwrite(self, partial);
         Ok(())
     }
+
+    /// Overwrite with `key.path=value` pairs, values which are not valid TOML are used as strings.
+    pub fn overwrite_values(&mut self, values: &[String]) -> Result<(), FormatError> {
+        let schema = Self::schema();
+        let mut table = toml::Table::new();
+        for entry in values {
+            let Some((path, value)) = entry.split_once('=') else {
+                return Err(FormatError::MissingSettingValue(entry.clone()));
+            };
+            if find_setting(&schema, path.trim()).is_none() {
+                return Err(FormatError::UnknownSetting(path.trim().into()));
+            }
+            let value = value.trim();
+            let value = toml::from_str::<toml::Table>(&format!("value = {value}"))
+                .ok()
+                .and_then(|mut table| table.remove("value"))
+                .unwrap_or_else(|| toml::Value::String(value.into()));
+
+            let mut keys = path.trim().rsplit('.');
+            let Some(last) = keys.next() else {
+                return Err(FormatError::MissingSettingValue(entry.clone()));
+            };
+            let mut nested = toml::Table::from_iter([(last.to_owned(), value)]);
+            for key in keys {
+                nested = toml::Table::from_iter([(key.to_owned(), toml::Value::Table(nested))]);
+            }
+            merge(&mut table, nested);
+        }
+        self.overwrite_str(&toml::to_string(&table)?)
+            .map_err(FormatError::InvalidSettingValue)
+    }
+}
+
+fn merge(table: &mut toml::Table, other: toml::Table) {
+    for (key, value) in other {
+        match (table.get_mut(&key), value) {
+            (Some(toml::Value::Table(existing)), toml::Value::Table(value)) => {
+                merge(existing, value)
+            }
+            (_, value) => {
+                table.insert(key, value);
+            }
+        }
+    }
 }
@@ -20,6 +20,8 @@ pub struct State {
     pub indentation: usize,
     pub extra_indentation: usize,
     pub mode: Mode,
+    /// Keep expressions on one line, used to measure their width
+    pub flat: bool,
 }
 
 impl State {
@@ -31,6 +33,7 @@ impl State {
                 0 => Mode::Markup,
                 _ => Mode::MarkupBreakable,
             },
+            flat: false,
         }
     }
 
@@ -4,124 +4,37 @@ use clap::ValueEnum;
 
 use crate::settings::*;
 
+const DEFAULT: &str = include_str!("styles/default.toml");
+const OTBS: &str = include_str!("styles/otbs.toml");
+const TYPSTYLE: &str = include_str!("styles/typstyle.toml");
+const COMPACT: &str = include_str!("styles/compact.toml");
+
 #[derive(Debug, Copy, Clone, ValueEnum)]
 pub enum Styles {
     /// Laurmaedje's style
     Default,
     /// One true bracket style
     Otbs,
+    /// Close to the output of typstyle
+    Typstyle,
+    /// No blank lines around headings and no argument expansion
+    Compact,
 }
 
 impl Styles {
     pub fn settings(&self) -> Settings {
-        match self {
-            Self::Default => Settings {
-                indentation: 2,
-                separate_label: true,
-                preserve_newline: PreserveNewLine {
-                    content: true,
-                    math: true,
-                },
-                automatic_newline: AutomaticNewLine {
-                    max_width: 100,
-                    in_strong: false,
-                    in_emphasis: false,
-                },
-                term: PaddingSettings {
-                    space_before: false,
-                    space_after: true,
-                },
-                named_argument: PaddingSettings {
-                    space_before: false,
-                    space_after: true,
-                },
-                dictionary_entry: PaddingSettings {
-                    space_before: false,
-                    space_after: true,
-                },
-                import_statement: PaddingSettings {
-                    space_before: false,
-                    space_after: true,
-                },
-                comma: PaddingSettings {
-                    space_before: false,
-                    space_after: true,
-                },
-                columns: ColumnsSettings {
-                    comma: AlignComma::EndOfContent,
-                },
-                block: BlockSettings {
-                    long_block_style: LongBlockStyle::Compact,
-                },
-                final_newline: true,
-                heading: HeadingSettings {
-                    blank_lines_before: 1,
-                    blank_lines_after: 0,
-                },
-
-                columns_methods: [
-                    (String::from("table"), String::from("columns")),
-                    (String::from("tablex"), String::from("columns")),
-                    (String::from("grid"), String::from("columns")),
-                    (String::from("gridx"), String::from("columns")),
-                ]
-                .into_iter()
-                .collect(),
-            },
-            Self::Otbs => Settings {
-                indentation: 0,
-                separate_label: true,
-                preserve_newline: PreserveNewLine {
-                    content: false,
-                    math: true,
-                },
-                automatic_newline: AutomaticNewLine {
-                    max_width: 0,
-                    in_strong: false,
-                    in_emphasis: false,
-                },
-                term: PaddingSettings {
-                    space_before: false,
-                    space_after: true,
-                },
-                named_argument: PaddingSettings {
-                    space_before: false,
-                    space_after: true,
-                },
-                dictionary_entry: PaddingSettings {
-                    space_before: false,
-                    space_after: true,
-                },
-                import_statement: PaddingSettings {
-                    space_before: false,
-                    space_after: true,
-                },
-                comma: PaddingSettings {
-                    space_before: false,
-                    space_after: true,
-                },
-                columns: ColumnsSettings {
-                    comma: AlignComma::EndOfContent,
-                },
-                block: BlockSettings {
-                    long_block_style: LongBlockStyle::Separate,
-                },
-                final_newline: true,
-                heading: HeadingSettings {
-                    blank_lines_before: 2,
-                    blank_lines_after: 1,
-                },
-
-                columns_methods: [
-                    (String::from("table"), String::from("columns")),
-                    (String::from("tablex"), String::from("columns")),
-                    (String::from("grid"), String::from("columns")),
-                    (String::from("gridx"), String::from("columns")),
-                ]
-                .into_iter()
-                .collect(),
-            },
-        }
+        let mut settings =
+            toml::from_str::<Settings>(DEFAULT).expect("default style sets every setting");
+        let style = match self {
+            Self::Default => return settings,
+            Self::Otbs => OTBS,
+            Self::Typstyle => TYPSTYLE,
+            Self::Compact => COMPACT,
+        };
+        settings
+            .overwrite_str(style)
+            .expect("bundled styles are valid configurations");
+        settings
     }
 }
 
@@ -130,6 +43,8 @@ impl Display for Styles {
         match self {
             Self::Default => write!(f, "default"),
             Self::Otbs => write!(f, "otbs"),
+            Self::Typstyle => write!(f, "typstyle"),
+            Self::Compact => write!(f, "compact"),
         }
     }
 }
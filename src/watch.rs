@@ -0,0 +1,125 @@
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    sync::mpsc,
+    time::{Duration, SystemTime},
+};
+
+use notify::{RecursiveMode, Watcher};
+
+use crate::{CONFIG_NAME, Command, FormatError, format};
+
+const SETTLE_TIME: Duration = Duration::from_millis(100);
+const EDITORCONFIG_NAME: &str = ".editorconfig";
+
+/// Format changed Typst files below `paths` until the process is stopped.
+///
+/// Changes are handled once no further change arrives for a short time, so rapid writes from
+/// editors are only formatted once. Changed configuration files, also in the parent folders of
+/// `paths`, reformat every watched file.
+pub fn watch(command: &Command, paths: &[PathBuf]) -> Result<(), FormatError> {
+    let paths = paths
+        .iter()
+        .map(std::path::absolute)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(FormatError::FailedToGetWorkingDirectory)?;
+
+    let (sender, receiver) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(sender).map_err(FormatError::FailedToWatch)?;
+    for path in &paths {
+        watcher
+            .watch(path, RecursiveMode::Recursive)
+            .map_err(FormatError::FailedToWatch)?;
+    }
+    // only the configuration files are used from the parent folders
+    let parents = paths
+        .iter()
+        .flat_map(|path| path.ancestors().skip(1))
+        .collect::<HashSet<_>>();
+    for parent in parents {
+        let _ = watcher.watch(parent, RecursiveMode::NonRecursive);
+    }
+    eprintln!("watching for changes");
+
+    // state after the own writes, which are not handled again
+    let mut written = HashMap::<PathBuf, (SystemTime, u64)>::new();
+    while let Ok(event) = receiver.recv() {
+        let mut changed = HashSet::<PathBuf>::new();
+        let mut event = Some(event);
+        while let Some(next) = event {
+            if let Ok(next) = next {
+                changed.extend(next.paths);
+            }
+            event = receiver.recv_timeout(SETTLE_TIME).ok();
+        }
+
+        let reload = changed.iter().any(|path| is_configuration(path));
+        let mut files = if reload {
+            let mut files = Vec::new();
+            for path in &paths {
+                collect(path, &mut files);
+            }
+            files
+        } else {
+            changed
+                .into_iter()
+                .filter(|path| {
+                    is_typst(path)
+                        && paths.iter().any(|watched| path.starts_with(watched))
+                        && written.get(path) != state(path).as_ref()
+                })
+                .collect()
+        };
+        files.retain(|path| path.is_file());
+        files.sort();
+
+        for file in files {
+            let mut file_command = command.clone();
+            file_command.path = Some(file.clone());
+            file_command.watch = None;
+            if let Err(err) = format(&file_command) {
+                eprintln!("{err}");
+            }
+            if let Some(state) = state(&file) {
+                written.insert(file, state);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Typst files below `path`, hidden folders like '.git' are skipped.
+fn collect(path: &Path, files: &mut Vec<PathBuf>) {
+    if !path.is_dir() {
+        if is_typst(path) {
+            files.push(path.to_owned());
+        }
+        return;
+    }
+    let Ok(entries) = std::fs::read_dir(path) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let hidden = path
+            .file_name()
+            .is_some_and(|name| name.to_string_lossy().starts_with('.'));
+        if !(path.is_dir() && hidden) {
+            collect(&path, files);
+        }
+    }
+}
+
+fn state(path: &Path) -> Option<(SystemTime, u64)> {
+    let metadata = std::fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+fn is_typst(path: &Path) -> bool {
+    path.extension().is_some_and(|extension| extension == "typ")
+}
+
+fn is_configuration(path: &Path) -> bool {
+    path.file_name()
+        .is_some_and(|name| name == CONFIG_NAME || name == EDITORCONFIG_NAME)
+}
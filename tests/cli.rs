@@ -0,0 +1,887 @@
+use std::{
+    ffi::OsStr,
+    io::{BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+    process::Stdio,
+};
+
+use clap::Parser;
+use prettypst::{Command, ExitCode, FormatError, Styles, format};
+
+fn temporary_folder(name: &str) -> PathBuf {
+    let folder = std::env::temp_dir().join(format!("prettypst-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&folder);
+    std::fs::create_dir_all(&folder).unwrap();
+    folder
+}
+
+fn run(folder: &Path, source: &str, arguments: &[&str]) -> Result<String, FormatError> {
+    let input = folder.join("input.typ");
+    let output = folder.join("output.typ");
+    std::fs::write(&input, source).unwrap();
+
+    let mut command = vec![
+        "prettypst".into(),
+        input.into_os_string(),
+        "--no-cache".into(),
+    ];
+    command.extend(arguments.iter().map(Into::into));
+    command.extend(["-o".into(), output.clone().into_os_string()]);
+    format(&Command::parse_from(command))?;
+    Ok(std::fs::read_to_string(output).unwrap())
+}
+
+#[test]
+fn set_overwrites_settings() {
+    let folder = temporary_folder("set");
+    let source = "#{\nlet x = 1\n}\n= Heading\n";
+
+    let output = run(
+        &folder,
+        source,
+        &[
+            "--style=otbs",
+            "--set",
+            "indentation=4",
+            "--set",
+            "heading.blank-lines-before=0",
+            "--set",
+            "final-newline = false",
+        ],
+    )
+    .unwrap();
+    assert_eq!(output, "#{\n    let x = 1\n}\n= Heading");
+
+    let output = run(&folder, source, &["--set", "line-ending=crlf"]).unwrap();
+    assert_eq!(output, "#{\r\n  let x = 1\r\n}\r\n\r\n= Heading\r\n");
+
+    assert!(matches!(
+        run(&folder, source, &["--set", "indentation"]),
+        Err(FormatError::MissingSettingValue(_)),
+    ));
+    assert!(matches!(
+        run(&folder, source, &["--set", "indentation=wide"]),
+        Err(FormatError::InvalidSettingValue(_)),
+    ));
+    assert!(matches!(
+        run(&folder, source, &["--set", "indentaton=4"]),
+        Err(FormatError::UnknownSetting(name)) if name == "indentaton",
+    ));
+    assert!(matches!(
+        run(&folder, source, &["--set", "heading.blank-lines=1"]),
+        Err(FormatError::UnknownSetting(_)),
+    ));
+
+    // entries of maps are added to the existing ones
+    let mut settings = Styles::Default.settings();
+    settings
+        .overwrite_values(&["columns-methods.figures=columns".into()])
+        .unwrap();
+    assert_eq!(settings.columns_methods["figures"], "columns");
+    assert_eq!(settings.columns_methods["table"], "columns");
+
+    std::fs::remove_dir_all(folder).unwrap();
+}
+
+#[test]
+fn stdin_filepath_for_configuration() {
+    let folder = temporary_folder("stdin-filepath");
+    std::fs::create_dir_all(folder.join("chapters")).unwrap();
+    std::fs::write(folder.join("prettypst.toml"), "indentation = 4\n").unwrap();
+
+    let mut child = std::process::Command::new(env!("CARGO_BIN_EXE_prettypst"))
+        .arg("--use-std-in")
+        .arg("--use-configuration")
+        .arg("--stdin-filepath")
+        .arg(folder.join("chapters").join("missing.typ"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(b"#{\nlet x = 1\n}\n")
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+
+    assert!(output.status.success());
+    assert_eq!(output.stdout, b"#{\n    let x = 1\n}\n");
+    assert!(!folder.join("chapters").join("missing.typ").exists());
+
+    std::fs::remove_dir_all(folder).unwrap();
+}
+
+#[test]
+fn fail_on_parse_error() {
+    let folder = temporary_folder("parse-error");
+    let source = "#let x = (1,\n";
+
+    assert!(run(&folder, source, &[]).is_ok());
+    std::fs::remove_file(folder.join("output.typ")).unwrap();
+
+    assert!(matches!(
+        run(&folder, source, &["--fail-on-parse-error"]),
+        Err(FormatError::ParseErrors(_)),
+    ));
+    assert!(!folder.join("output.typ").exists());
+
+    std::fs::remove_dir_all(folder).unwrap();
+}
+
+#[test]
+fn json_report() {
+    let folder = temporary_folder("report");
+    let input = folder.join("input.typ");
+    std::fs::write(&input, "= Title\n#f(a,b)\n\nText\n#g(c)\n").unwrap();
+
+    let report = || {
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_prettypst"))
+            .arg(&input)
+            .args(["--report", "json", "--no-cache"])
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        serde_json::from_slice::<serde_json::Value>(&output.stdout).unwrap()
+    };
+
+    let first = report();
+    assert_eq!(first["version"], prettypst::REPORT_VERSION);
+    assert_eq!(first["path"], input.display().to_string());
+    assert_eq!(first["changed"], true);
+    assert_eq!(
+        first["changed_lines"],
+        serde_json::json!([{ "start": 2, "end": 2 }])
+    );
+    assert_eq!(first["parse_errors"], serde_json::json!([]));
+    assert_eq!(first["configuration"], serde_json::Value::Null);
+    assert!(first["duration_ms"].is_number());
+
+    let second = report();
+    assert_eq!(second["changed"], false);
+    assert_eq!(second["changed_lines"], serde_json::json!([]));
+
+    // the document and the report are not both written to standard output
+    let from_std_in = |arguments: &[&str]| {
+        let mut child = std::process::Command::new(env!("CARGO_BIN_EXE_prettypst"))
+            .args(["--use-std-in", "--report", "json"])
+            .args(arguments)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+        child.stdin.take().unwrap().write_all(b"#f(a,b)\n").unwrap();
+        child.wait_with_output().unwrap()
+    };
+    let output = from_std_in(&[]);
+    assert_eq!(output.status.code(), Some(ExitCode::Usage as i32));
+    assert!(output.stdout.is_empty());
+    let output = from_std_in(&["--check"]);
+    assert_eq!(output.status.code(), Some(ExitCode::ChangesNeeded as i32));
+    let report = serde_json::from_slice::<serde_json::Value>(&output.stdout).unwrap();
+    assert_eq!(report["changed"], true);
+
+    std::fs::remove_dir_all(folder).unwrap();
+}
+
+#[test]
+fn exit_codes() {
+    let folder = temporary_folder("exit-codes");
+    let formatted = folder.join("formatted.typ");
+    let unformatted = folder.join("unformatted.typ");
+    let erroneous = folder.join("erroneous.typ");
+    std::fs::write(&formatted, "#f(a, b)\n").unwrap();
+    std::fs::write(&unformatted, "#f(a,b)\n").unwrap();
+    std::fs::write(&erroneous, "#f(a,\n").unwrap();
+    std::fs::write(folder.join("prettypst.toml"), "indentation = \"wide\"\n").unwrap();
+
+    let code = |arguments: &[&OsStr]| {
+        std::process::Command::new(env!("CARGO_BIN_EXE_prettypst"))
+            .args(arguments)
+            .arg("--no-cache")
+            .current_dir(&folder)
+            .output()
+            .unwrap()
+            .status
+            .code()
+            .unwrap()
+    };
+    let check = OsStr::new("--check");
+
+    assert_eq!(
+        code(&[check, formatted.as_os_str()]),
+        ExitCode::Success as i32
+    );
+    assert_eq!(
+        code(&[check, unformatted.as_os_str()]),
+        ExitCode::ChangesNeeded as i32,
+    );
+    assert_eq!(
+        code(&[formatted.as_os_str(), OsStr::new("--use-std-in")]),
+        ExitCode::Usage as i32,
+    );
+    assert_eq!(
+        code(&[
+            check,
+            erroneous.as_os_str(),
+            OsStr::new("--fail-on-parse-error"),
+        ]),
+        ExitCode::ParseError as i32,
+    );
+    assert_eq!(
+        code(&[
+            check,
+            formatted.as_os_str(),
+            OsStr::new("--use-configuration"),
+        ]),
+        ExitCode::ConfigurationError as i32,
+    );
+    assert_eq!(
+        code(&[check, folder.join("missing.typ").as_os_str()]),
+        ExitCode::IoError as i32,
+    );
+    // the folder is no git repository
+    assert_eq!(
+        code(&[OsStr::new("--changed-since"), OsStr::new("HEAD")]),
+        ExitCode::IoError as i32,
+    );
+
+    // check mode never writes
+    assert_eq!(std::fs::read_to_string(&unformatted).unwrap(), "#f(a,b)\n");
+
+    std::fs::remove_dir_all(folder).unwrap();
+}
+
+#[test]
+fn multiple_files() {
+    let folder = temporary_folder("multiple-files");
+    let files = [folder.join("a.typ"), folder.join("b.typ")];
+    for file in &files {
+        std::fs::write(file, "#f(a,b)\n").unwrap();
+    }
+    let format_files = |arguments: &[&OsStr]| {
+        let mut command = vec![OsStr::new("prettypst"), OsStr::new("--no-cache")];
+        command.extend(arguments);
+        command.extend(files.iter().map(|file| file.as_os_str()));
+        format(&Command::parse_from(command))
+    };
+
+    assert!(matches!(
+        format_files(&[OsStr::new("--check")]),
+        Err(FormatError::Files(errors)) if errors.len() == 2,
+    ));
+    format_files(&[]).unwrap();
+    for file in &files {
+        assert_eq!(std::fs::read_to_string(file).unwrap(), "#f(a, b)\n");
+    }
+    format_files(&[OsStr::new("--check")]).unwrap();
+
+    std::fs::remove_dir_all(folder).unwrap();
+}
+
+#[test]
+fn in_place_replacement() {
+    let folder = temporary_folder("in-place");
+    let file = folder.join("main.typ");
+    let format_in_place = |path: &Path| {
+        format(&Command::parse_from([
+            OsStr::new("prettypst"),
+            path.as_os_str(),
+            OsStr::new("--no-cache"),
+        ]))
+    };
+
+    std::fs::write(&file, "#{\nlet x = 1\n}\n").unwrap();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&file, std::fs::Permissions::from_mode(0o640)).unwrap();
+    }
+    format_in_place(&file).unwrap();
+    assert_eq!(
+        std::fs::read_to_string(&file).unwrap(),
+        "#{\n  let x = 1\n}\n"
+    );
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(&file).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o640);
+
+        // the permissions are set before the content is written
+        let read_only = folder.join("read-only.typ");
+        std::fs::write(&read_only, "#{\nlet x = 1\n}\n").unwrap();
+        std::fs::set_permissions(&read_only, std::fs::Permissions::from_mode(0o440)).unwrap();
+        format_in_place(&read_only).unwrap();
+        let mode = std::fs::metadata(&read_only).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o440);
+        assert_eq!(
+            std::fs::read_to_string(&read_only).unwrap(),
+            "#{\n  let x = 1\n}\n"
+        );
+    }
+
+    // formatted files are not written again
+    let modified = std::fs::metadata(&file).unwrap().modified().unwrap();
+    std::thread::sleep(std::time::Duration::from_millis(20));
+    format_in_place(&file).unwrap();
+    assert_eq!(
+        std::fs::metadata(&file).unwrap().modified().unwrap(),
+        modified
+    );
+
+    #[cfg(unix)]
+    {
+        let link = folder.join("link.typ");
+        std::fs::write(&file, "#{\nlet y = 2\n}\n").unwrap();
+        std::os::unix::fs::symlink(&file, &link).unwrap();
+        format_in_place(&link).unwrap();
+        assert!(
+            std::fs::symlink_metadata(&link)
+                .unwrap()
+                .file_type()
+                .is_symlink()
+        );
+        assert_eq!(
+            std::fs::read_to_string(&file).unwrap(),
+            "#{\n  let y = 2\n}\n"
+        );
+    }
+
+    // no temporary files are left behind
+    assert!(std::fs::read_dir(&folder).unwrap().all(|entry| {
+        !entry
+            .unwrap()
+            .file_name()
+            .to_string_lossy()
+            .ends_with(".tmp")
+    }));
+
+    std::fs::remove_dir_all(folder).unwrap();
+}
+
+#[test]
+fn cache() {
+    let folder = temporary_folder("cache");
+    let cache = folder.join("cache");
+    let file = folder.join("main.typ");
+    let format_with = |arguments: &[&str]| {
+        let mut command = vec![OsStr::new("prettypst"), file.as_os_str()];
+        command.extend(["--cache-dir".as_ref(), cache.as_os_str()]);
+        command.extend(arguments.iter().map(OsStr::new));
+        format(&Command::parse_from(command))
+    };
+    let version = cache.join("formatted").join(env!("CARGO_PKG_VERSION"));
+    let shards = || match std::fs::read_dir(&version) {
+        Ok(shards) => shards
+            .map(|shard| shard.unwrap().path())
+            .collect::<Vec<_>>(),
+        Err(_) => Vec::new(),
+    };
+    let entries = || {
+        let mut entries = shards()
+            .into_iter()
+            .flat_map(|shard| std::fs::read_dir(shard).unwrap())
+            .map(|entry| entry.unwrap().path())
+            .collect::<Vec<_>>();
+        entries.sort();
+        entries
+    };
+
+    std::fs::write(&file, "#{\n  let x = 1\n}\n").unwrap();
+    format_with(&["--no-cache"]).unwrap();
+    assert!(entries().is_empty());
+    format_with(&[]).unwrap();
+    let first = entries();
+    assert_eq!(first.len(), 1);
+
+    // the key is the same for every run, so later runs hit the entry
+    for _ in 0..3 {
+        format_with(&["--check"]).unwrap();
+        assert_eq!(entries(), first);
+    }
+
+    // the settings are part of the key
+    format_with(&["--set", "indentation=4"]).unwrap();
+    assert_eq!(
+        std::fs::read_to_string(&file).unwrap(),
+        "#{\n    let x = 1\n}\n"
+    );
+    assert!(matches!(
+        format_with(&["--check"]),
+        Err(FormatError::NotFormatted(_)),
+    ));
+
+    // the written output is cached as well
+    let second = entries();
+    assert_eq!(second.len(), 2);
+    format_with(&["--check", "--set", "indentation=4"]).unwrap();
+    assert_eq!(entries(), second);
+
+    let clear = || {
+        let command = Command::parse_from([
+            OsStr::new("prettypst"),
+            OsStr::new("--clear-cache"),
+            OsStr::new("--cache-dir"),
+            cache.as_os_str(),
+        ]);
+        format(&command).unwrap();
+    };
+    clear();
+    assert!(entries().is_empty());
+    assert!(cache.is_dir());
+
+    // entries of other versions are removed
+    let other = cache.join("formatted").join("0.0.0");
+    std::fs::create_dir_all(other.join("00")).unwrap();
+    std::fs::write(other.join("00").join("00000000000000"), "").unwrap();
+    format_with(&[]).unwrap();
+    assert!(!other.exists());
+    let [entry] = entries().try_into().unwrap();
+
+    // full shards drop their oldest entry
+    clear();
+    let shard = entry.parent().unwrap();
+    std::fs::create_dir_all(shard).unwrap();
+    for index in 0..64 {
+        std::fs::write(shard.join(format!("{index:014x}")), "").unwrap();
+    }
+    format_with(&[]).unwrap();
+    let entries = entries();
+    assert_eq!(entries.len(), 64);
+    assert!(entries.contains(&entry));
+    assert!(!entries.contains(&shard.join(format!("{:014x}", 0))));
+
+    std::fs::remove_dir_all(folder).unwrap();
+}
+
+fn git(repository: &Path, arguments: &[&str]) -> String {
+    let output = std::process::Command::new("git")
+        .args([
+            "-c",
+            "user.name=prettypst",
+            "-c",
+            "user.email=prettypst@example.com",
+        ])
+        .args(arguments)
+        .current_dir(repository)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "git {:?} failed", arguments);
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn changed_files() {
+    let repository = temporary_folder("changed-files");
+    std::fs::create_dir_all(repository.join("chapters")).unwrap();
+    let unformatted = "#{\nlet x = 1\n}\n";
+    let formatted = "#{\n  let x = 1\n}\n";
+    for name in ["legacy.typ", "changed.typ", "chapters/partial.typ"] {
+        std::fs::write(repository.join(name), unformatted).unwrap();
+    }
+    git(&repository, &["init", "--quiet"]);
+    git(&repository, &["add", "."]);
+    git(&repository, &["commit", "--quiet", "-m", "initial"]);
+
+    let prettypst = |arguments: &[&str]| {
+        std::process::Command::new(env!("CARGO_BIN_EXE_prettypst"))
+            .args(arguments)
+            .env("XDG_CACHE_HOME", repository.join(".git"))
+            .current_dir(repository.join("chapters"))
+            .status()
+            .unwrap()
+    };
+
+    std::fs::write(repository.join("changed.typ"), format!("{unformatted}#x\n")).unwrap();
+    assert!(prettypst(&["--changed-since", "HEAD"]).success());
+    assert_eq!(
+        std::fs::read_to_string(repository.join("changed.typ")).unwrap(),
+        format!("{formatted}#x\n"),
+    );
+    assert_eq!(
+        std::fs::read_to_string(repository.join("legacy.typ")).unwrap(),
+        unformatted,
+    );
+
+    // only the staged version is added to the index
+    git(&repository, &["add", "changed.typ"]);
+    std::fs::write(
+        repository.join("chapters/partial.typ"),
+        format!("{unformatted}#y\n"),
+    )
+    .unwrap();
+    git(&repository, &["add", "chapters/partial.typ"]);
+    std::fs::write(
+        repository.join("chapters/partial.typ"),
+        format!("{unformatted}#y\n#z\n"),
+    )
+    .unwrap();
+    let status = prettypst(&["--staged", "--check"]);
+    assert_eq!(status.code(), Some(ExitCode::ChangesNeeded as i32));
+    assert!(prettypst(&["--staged"]).success());
+    assert_eq!(
+        git(&repository, &["show", ":chapters/partial.typ"]),
+        format!("{formatted}#y\n"),
+    );
+    assert_eq!(
+        std::fs::read_to_string(repository.join("chapters/partial.typ")).unwrap(),
+        format!("{formatted}#y\n#z\n"),
+    );
+    assert_eq!(
+        git(&repository, &["diff", "--cached", "--name-only"]),
+        "changed.typ\nchapters/partial.typ\n",
+    );
+
+    // the index is checked instead of the working tree
+    let partial = repository.join("chapters/partial.typ");
+    std::fs::write(&partial, format!("{unformatted}#y\n#z\n")).unwrap();
+    assert!(prettypst(&["--staged", "--check"]).success());
+    git(&repository, &["add", "chapters/partial.typ"]);
+    std::fs::write(&partial, format!("{formatted}#y\n#z\n")).unwrap();
+    let status = prettypst(&["--staged", "--check"]);
+    assert_eq!(status.code(), Some(ExitCode::ChangesNeeded as i32));
+    assert_eq!(
+        git(&repository, &["show", ":chapters/partial.typ"]),
+        format!("{unformatted}#y\n#z\n"),
+    );
+
+    std::fs::remove_dir_all(repository).unwrap();
+}
+
+#[test]
+fn format_lines() {
+    let folder = temporary_folder("lines");
+    let source = "Some   text\n#f(a,b)\n\nOther   text\n#{\nlet x=1\n  let y   =   2\n}\n";
+
+    let output = run(&folder, source, &["--lines", "2"]).unwrap();
+    assert_eq!(
+        output,
+        "Some   text\n#f(a, b)\n\nOther   text\n#{\nlet x=1\n  let y   =   2\n}\n",
+    );
+    let output = run(&folder, source, &["--lines", "7-7,20"]).unwrap();
+    assert_eq!(
+        output,
+        "Some   text\n#f(a,b)\n\nOther   text\n#{\nlet x=1\n  let y = 2\n}\n",
+    );
+    // the hash is part of the embedded code block
+    let output = run(&folder, source, &["--lines", "5"]).unwrap();
+    assert_eq!(
+        output,
+        "Some   text\n#f(a,b)\n\nOther   text\n#{\n  let x = 1\n  let y = 2\n}\n",
+    );
+
+    assert!(Command::try_parse_from(["prettypst", "--lines", "4-2"]).is_err());
+    assert!(Command::try_parse_from(["prettypst", "--lines", "0"]).is_err());
+
+    std::fs::remove_dir_all(folder).unwrap();
+}
+
+#[test]
+fn only_changed_lines() {
+    let repository = temporary_folder("only-changed-lines");
+    let source = "#f(a,b)\n\n#g(a,b)\n";
+    std::fs::write(repository.join("main.typ"), source).unwrap();
+    git(&repository, &["init", "--quiet"]);
+    git(&repository, &["add", "."]);
+    git(&repository, &["commit", "--quiet", "-m", "initial"]);
+
+    std::fs::write(repository.join("main.typ"), "#f(a,b)\n\n#g(a,b,c)\n").unwrap();
+    let status = std::process::Command::new(env!("CARGO_BIN_EXE_prettypst"))
+        .args(["--changed-since", "HEAD", "--only-changed-lines"])
+        .env("XDG_CACHE_HOME", repository.join(".git"))
+        .current_dir(&repository)
+        .status()
+        .unwrap();
+    assert!(status.success());
+    assert_eq!(
+        std::fs::read_to_string(repository.join("main.typ")).unwrap(),
+        "#f(a,b)\n\n#g(a, b, c)\n",
+    );
+
+    std::fs::remove_dir_all(repository).unwrap();
+}
+
+fn wait_for(path: &Path, expected: &str) -> String {
+    let mut content = String::new();
+    for _ in 0..50 {
+        content = std::fs::read_to_string(path).unwrap_or_default();
+        if content == expected {
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(100));
+    }
+    content
+}
+
+#[test]
+fn watch() {
+    let folder = temporary_folder("watch");
+    let chapters = folder.join("chapters");
+    let file = chapters.join("main.typ");
+    std::fs::create_dir_all(&chapters).unwrap();
+    std::fs::write(folder.join("prettypst.toml"), "indentation = 2\n").unwrap();
+
+    // the configuration is in a parent folder of the watched one
+    let mut child = std::process::Command::new(env!("CARGO_BIN_EXE_prettypst"))
+        .arg("--use-configuration")
+        .arg("--no-cache")
+        .arg("--watch")
+        .arg(&chapters)
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut ready = String::new();
+    BufReader::new(child.stderr.take().unwrap())
+        .read_line(&mut ready)
+        .unwrap();
+    assert_eq!(ready, "watching for changes\n");
+
+    std::fs::write(&file, "#{\nlet x = 1\n}\n").unwrap();
+    let formatted = wait_for(&file, "#{\n  let x = 1\n}\n");
+
+    // settings are reloaded
+    std::fs::write(folder.join("prettypst.toml"), "indentation = 4\n").unwrap();
+    let reloaded = wait_for(&file, "#{\n    let x = 1\n}\n");
+
+    child.kill().unwrap();
+    child.wait().unwrap();
+    assert_eq!(formatted, "#{\n  let x = 1\n}\n");
+    assert_eq!(reloaded, "#{\n    let x = 1\n}\n");
+
+    std::fs::remove_dir_all(folder).unwrap();
+}
+
+#[test]
+fn install_hook() {
+    let repository = temporary_folder("install-hook");
+    git(&repository, &["init", "--quiet"]);
+    let prettypst = |arguments: &[&str]| {
+        std::process::Command::new(env!("CARGO_BIN_EXE_prettypst"))
+            .args(arguments)
+            .env("XDG_CACHE_HOME", repository.join(".git"))
+            .current_dir(&repository)
+            .status()
+            .unwrap()
+    };
+    let commit = || {
+        // the hook calls the built binary
+        let binary = Path::new(env!("CARGO_BIN_EXE_prettypst")).parent().unwrap();
+        let path = std::env::join_paths(
+            std::iter::once(binary.to_owned())
+                .chain(std::env::split_paths(&std::env::var_os("PATH").unwrap())),
+        )
+        .unwrap();
+        std::process::Command::new("git")
+            .args([
+                "-c",
+                "user.name=prettypst",
+                "-c",
+                "user.email=prettypst@example.com",
+            ])
+            .args(["commit", "--quiet", "-m", "change"])
+            .env("PATH", path)
+            .env("XDG_CACHE_HOME", repository.join(".git"))
+            .current_dir(&repository)
+            .output()
+            .unwrap()
+    };
+
+    let hook = repository.join(".git").join("hooks").join("pre-commit");
+    std::fs::create_dir_all(hook.parent().unwrap()).unwrap();
+    std::fs::write(&hook, "#!/bin/sh\n").unwrap();
+    assert_eq!(
+        prettypst(&["install-hook"]).code(),
+        Some(ExitCode::Usage as i32),
+    );
+    assert!(prettypst(&["install-hook", "--force"]).success());
+    assert!(prettypst(&["install-hook", "--", "--set", "indentation=4"]).success());
+
+    std::fs::write(repository.join("main.typ"), "#{\nlet x = 1\n}\n").unwrap();
+    git(&repository, &["add", "main.typ"]);
+    let output = commit();
+    assert!(!output.status.success());
+    let message = String::from_utf8_lossy(&output.stderr);
+    assert!(message.contains("is not formatted"), "{message}");
+    assert!(
+        message.contains("prettypst --staged '--set' 'indentation=4'"),
+        "{message}"
+    );
+
+    assert!(prettypst(&["--staged", "--set", "indentation=4"]).success());
+    assert!(commit().status.success());
+    assert_eq!(
+        git(&repository, &["show", "HEAD:main.typ"]),
+        "#{\n    let x = 1\n}\n",
+    );
+
+    std::fs::remove_dir_all(repository).unwrap();
+}
+
+#[test]
+fn subcommands() {
+    let folder = temporary_folder("subcommands");
+    let file = folder.join("main.typ");
+    std::fs::write(folder.join("prettypst.toml"), "indentation = 4\n").unwrap();
+    std::fs::write(&file, "#{\nlet x = 1\n}\n").unwrap();
+    let prettypst = |arguments: &[&OsStr]| {
+        std::process::Command::new(env!("CARGO_BIN_EXE_prettypst"))
+            .args(arguments)
+            .env("XDG_CACHE_HOME", &folder)
+            .current_dir(&folder)
+            .output()
+            .unwrap()
+    };
+
+    let output = prettypst(&["check".as_ref(), file.as_os_str()]);
+    assert_eq!(output.status.code(), Some(ExitCode::ChangesNeeded as i32));
+    let formatted = folder.join("formatted.typ");
+    std::fs::write(&formatted, "#f(a, b)\n").unwrap();
+    let output = prettypst(&["check".as_ref(), formatted.as_os_str(), file.as_os_str()]);
+    assert_eq!(output.status.code(), Some(ExitCode::ChangesNeeded as i32));
+    assert!(
+        prettypst(&["check".as_ref(), formatted.as_os_str()])
+            .status
+            .success()
+    );
+
+    let output = prettypst(&[
+        "diff".as_ref(),
+        "--use-configuration".as_ref(),
+        file.as_os_str(),
+    ]);
+    assert_eq!(output.status.code(), Some(ExitCode::ChangesNeeded as i32));
+    let diff = String::from_utf8(output.stdout).unwrap();
+    assert!(diff.contains("-let x = 1\n+    let x = 1\n"), "{diff}");
+    assert_eq!(
+        std::fs::read_to_string(&file).unwrap(),
+        "#{\nlet x = 1\n}\n"
+    );
+
+    let output = prettypst(&["config".as_ref(), "path".as_ref(), file.as_os_str()]);
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap().trim_end(),
+        folder.join("prettypst.toml").display().to_string(),
+    );
+    let output = prettypst(&[
+        "--use-configuration".as_ref(),
+        "config".as_ref(),
+        "show".as_ref(),
+    ]);
+    assert!(
+        String::from_utf8(output.stdout)
+            .unwrap()
+            .starts_with("indentation = 4\n")
+    );
+
+    let output = prettypst(&["explain".as_ref(), "heading.blank-lines-before".as_ref()]);
+    assert!(
+        String::from_utf8(output.stdout)
+            .unwrap()
+            .contains("type: integer")
+    );
+    let output = prettypst(&["explain".as_ref(), "heading.missing".as_ref()]);
+    assert_eq!(output.status.code(), Some(ExitCode::Usage as i32));
+
+    // the subcommand and the option are the same
+    assert!(
+        prettypst(&[
+            "format".as_ref(),
+            "--use-configuration".as_ref(),
+            file.as_os_str()
+        ])
+        .status
+        .success()
+    );
+    assert_eq!(
+        std::fs::read_to_string(&file).unwrap(),
+        "#{\n    let x = 1\n}\n"
+    );
+    assert!(
+        prettypst(&[
+            "--check".as_ref(),
+            "--use-configuration".as_ref(),
+            file.as_os_str()
+        ])
+        .status
+        .success()
+    );
+
+    std::fs::remove_dir_all(folder).unwrap();
+}
+
+#[test]
+fn language_server() {
+    let folder = temporary_folder("language-server");
+    let uri = format!("file://{}", folder.join("main%20file.typ").display()).replace(' ', "%20");
+    let messages = [
+        serde_json::json!({ "jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {} }),
+        serde_json::json!({ "jsonrpc": "2.0", "method": "textDocument/didOpen", "params": {
+            "textDocument": { "uri": uri, "languageId": "typst", "version": 1, "text": "#f(a,b)\n\n#g(a,b)" },
+        }}),
+        serde_json::json!({ "jsonrpc": "2.0", "id": 2, "method": "textDocument/formatting", "params": {
+            "textDocument": { "uri": uri }, "options": { "tabSize": 2, "insertSpaces": true },
+        }}),
+        serde_json::json!({ "jsonrpc": "2.0", "id": 3, "method": "textDocument/rangeFormatting", "params": {
+            "textDocument": { "uri": uri },
+            "range": { "start": { "line": 2, "character": 0 }, "end": { "line": 2, "character": 7 } },
+            "options": { "tabSize": 2, "insertSpaces": true },
+        }}),
+        serde_json::json!({ "jsonrpc": "2.0", "id": 4, "method": "textDocument/rangeFormatting", "params": {
+            "textDocument": { "uri": uri },
+            "range": { "start": { "line": 0, "character": 0 }, "end": { "line": 2, "character": 0 } },
+            "options": { "tabSize": 2, "insertSpaces": true },
+        }}),
+        serde_json::json!({ "jsonrpc": "2.0", "id": 5, "method": "shutdown" }),
+        serde_json::json!({ "jsonrpc": "2.0", "method": "exit" }),
+    ];
+    let input = messages
+        .iter()
+        .map(|message| {
+            let content = message.to_string();
+            format!("Content-Length: {}\r\n\r\n{}", content.len(), content)
+        })
+        .collect::<String>();
+
+    let mut child = std::process::Command::new(env!("CARGO_BIN_EXE_prettypst"))
+        .arg("lsp")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+
+    let output = String::from_utf8(output.stdout).unwrap();
+    let responses = output
+        .split("Content-Length: ")
+        .filter_map(|message| message.split_once("\r\n\r\n"))
+        .map(|(_, content)| serde_json::from_str::<serde_json::Value>(content).unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(responses.len(), 5);
+    assert_eq!(
+        responses[0]["result"]["capabilities"]["documentFormattingProvider"],
+        true,
+    );
+    assert_eq!(
+        responses[1]["result"][0]["newText"],
+        "#f(a, b)\n\n#g(a, b)\n"
+    );
+    assert_eq!(
+        responses[1]["result"][0]["range"]["end"],
+        serde_json::json!({ "line": 2, "character": 7 }),
+    );
+    assert_eq!(responses[2]["result"][0]["newText"], "#f(a,b)\n\n#g(a, b)");
+    // the range ends before the last line
+    assert_eq!(responses[3]["result"][0]["newText"], "#f(a, b)\n\n#g(a,b)");
+    assert_eq!(responses[4]["result"], serde_json::Value::Null);
+
+    std::fs::remove_dir_all(folder).unwrap();
+}
@@ -0,0 +1,33 @@
+use std::io::Cursor;
+
+use prettypst::{Styles, format_str};
+
+#[test]
+fn parse_errors_with_location() {
+    let source = "= Title\n#let x = (1,\n\nText\n";
+    let mut output = Cursor::new(Vec::new());
+    let errors = format_str(source, &Styles::Default.settings(), &mut output);
+
+    assert!(!errors.is_empty());
+    let error = &errors[0];
+    assert_eq!((error.line, error.column), (2, 10));
+    assert_eq!(&source[error.range.clone()], "(");
+
+    let report = error.report("main.typ", source);
+    assert!(report.contains("--> main.typ:2:10"), "{report}");
+    assert!(
+        report.contains("2 | #let x = (1,\n  |          ^\n"),
+        "{report}"
+    );
+}
+
+#[test]
+fn no_parse_errors() {
+    let mut output = Cursor::new(Vec::new());
+    let errors = format_str(
+        "#let x = (1, 2)\n",
+        &Styles::Default.settings(),
+        &mut output,
+    );
+    assert!(errors.is_empty());
+}
@@ -0,0 +1,54 @@
+use std::path::PathBuf;
+
+use clap::Parser;
+use prettypst::{Command, format};
+
+fn temporary_folder(name: &str) -> PathBuf {
+    let folder = std::env::temp_dir().join(format!("prettypst-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&folder);
+    std::fs::create_dir_all(&folder).unwrap();
+    folder
+}
+
+#[test]
+fn editorconfig_sections() {
+    let root = temporary_folder("editorconfig");
+    let project = root.join("project");
+    std::fs::create_dir_all(project.join("src")).unwrap();
+
+    // ignored, because the project config is marked as root
+    std::fs::write(root.join(".editorconfig"), "[*]\nindent_size = 8\n").unwrap();
+    std::fs::write(
+        project.join(".editorconfig"),
+        "root = true\n\n[*]\nindent_style = tab\n\n[src/**.{typ,md}]\nindent_style = space\nindent_size = 4\ninsert_final_newline = false\n",
+    )
+    .unwrap();
+
+    let source = "#{\nlet x = 1\n}\n";
+    std::fs::write(project.join("main.typ"), source).unwrap();
+    std::fs::write(project.join("src").join("main.typ"), source).unwrap();
+
+    for path in [
+        project.join("main.typ"),
+        project.join("src").join("main.typ"),
+    ] {
+        let command = Command::parse_from([
+            "prettypst".into(),
+            "--use-editorconfig".into(),
+            "--no-cache".into(),
+            path.into_os_string(),
+        ]);
+        format(&command).unwrap();
+    }
+
+    assert_eq!(
+        std::fs::read_to_string(project.join("main.typ")).unwrap(),
+        "#{\n\tlet x = 1\n}\n",
+    );
+    assert_eq!(
+        std::fs::read_to_string(project.join("src").join("main.typ")).unwrap(),
+        "#{\n    let x = 1\n}",
+    );
+
+    std::fs::remove_dir_all(root).unwrap();
+}
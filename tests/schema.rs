@@ -0,0 +1,40 @@
+use clap::ValueEnum;
+use prettypst::{Styles, settings_schema};
+use serde_json::Value;
+
+fn assert_covered(schema: &Value, values: &Value, path: &str) {
+    let Value::Object(values) = values else {
+        return;
+    };
+    let Some(properties) = schema.get("properties") else {
+        // maps like `columns-methods` have no fixed properties
+        return;
+    };
+    for (name, value) in values {
+        let path = format!("{path}.{name}");
+        let property = properties
+            .get(name)
+            .unwrap_or_else(|| panic!("{path} missing in schema"));
+        assert!(
+            property.get("properties").is_some() || property.get("default").is_some(),
+            "{path} has no default",
+        );
+        assert_covered(property, value, &path);
+    }
+}
+
+#[test]
+fn all_settings_in_schema() {
+    let schema = settings_schema();
+    for style in Styles::value_variants() {
+        let values = serde_json::to_value(style.settings()).unwrap();
+        assert_covered(&schema, &values, "settings");
+    }
+}
+
+#[test]
+fn enum_variants_in_schema() {
+    let schema = settings_schema();
+    let variants = &schema["properties"]["block"]["properties"]["long-block-style"]["enum"];
+    assert_eq!(variants, &serde_json::json!(["compact", "separate"]));
+}
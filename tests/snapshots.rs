@@ -1,6 +1,8 @@
 use std::{io::Cursor, path::Path};
 
-use prettypst::{format_node, Styles};
+use clap::ValueEnum;
+use prettypst::{Styles, format_node};
+use typst_syntax::{SyntaxNode, ast};
 
 macro_rules! test_styles {
     ($input_data:expr) => {
@@ -11,12 +13,12 @@ macro_rules! test_styles {
         settings.set_prepend_module_to_snapshot(false);
         settings.set_snapshot_path(Path::new("snapshots"));
 
-        for (style_name, style) in [("default", Styles::Default), ("otbs", Styles::Otbs)] {
+        for style in Styles::value_variants() {
             let mut output = Cursor::new(Vec::new());
             format_node(&root, &style.settings(), &mut output);
             let output = output.into_inner();
 
-            settings.set_snapshot_suffix(style_name);
+            settings.set_snapshot_suffix(style.to_string());
             settings.bind(|| {
                 insta::assert_binary_snapshot!(".typ", output);
             })
@@ -24,6 +26,29 @@ macro_rules! test_styles {
     };
 }
 
+/// Default style with some settings overwritten, e.g. `"chain.max-width=80"`.
+macro_rules! test_settings {
+    ($input_data:expr, $suffix:expr, $values:expr) => {
+        let input_data = include_str!(concat!("source/", $input_data, ".typ"));
+        let root = typst_syntax::parse(input_data);
+
+        let mut settings = insta::Settings::clone_current();
+        settings.set_prepend_module_to_snapshot(false);
+        settings.set_snapshot_path(Path::new("snapshots"));
+
+        let mut style = Styles::Default.settings();
+        style.overwrite_values(&$values.map(String::from)).unwrap();
+        let mut output = Cursor::new(Vec::new());
+        format_node(&root, &style, &mut output);
+        let output = output.into_inner();
+
+        settings.set_snapshot_suffix($suffix);
+        settings.bind(|| {
+            insta::assert_binary_snapshot!(concat!($input_data, "_settings.typ"), output);
+        })
+    };
+}
+
 macro_rules! create_tests {
     (
        	$(($name:ident, $file:expr),)*
@@ -38,20 +63,184 @@ macro_rules! create_tests {
 }
 
 create_tests!(
+    (alignment, "alignment"),
+    (binary, "binary"),
+    (chain, "chain"),
+    (closure, "closure"),
     (columns, "columns"),
     (comments, "comments"),
+    (control_flow, "control_flow"),
     (example, "example"),
     (headings, "headings"),
+    (imports, "imports"),
     (label, "label"),
     (long, "long"),
     (math, "math"),
+    (numbers, "numbers"),
+    (parentheses, "parentheses"),
+    (semicolons, "semicolons"),
     (single_argument, "single_argument"),
+    (strings, "strings"),
     (term, "term"),
     (shebang_heading, "shebang/heading"),
     (shebang_linebreak, "shebang/linebreak"),
     (shebang_parbreak, "shebang/parbreak"),
 );
 
+#[test]
+fn alignment_settings() {
+    test_settings!(
+        "alignment",
+        "aligned",
+        [
+            "alignment.let-bindings=true",
+            "alignment.named-entries=true",
+        ]
+    );
+}
+
+#[test]
+fn closure_settings() {
+    test_settings!(
+        "closure",
+        "parentheses-always",
+        ["closure.single-parameter-parentheses=always"]
+    );
+    test_settings!(
+        "closure",
+        "parentheses-never",
+        ["closure.single-parameter-parentheses=never"]
+    );
+    test_settings!("closure", "hug", ["closure.hug-last-argument=true"]);
+}
+
+#[test]
+fn control_flow_settings() {
+    test_settings!(
+        "control_flow",
+        "else-new-line",
+        ["control-flow.else-position=new-line"]
+    );
+}
+
+#[test]
+fn imports_settings() {
+    test_settings!(
+        "imports",
+        "sorted",
+        [
+            "imports.sort-items=true",
+            "imports.deduplicate-items=true",
+            "imports.group-statements=true",
+        ]
+    );
+}
+
+#[test]
+fn numbers_settings() {
+    test_settings!(
+        "numbers",
+        "normalized",
+        [
+            "numbers.leading-zero=true",
+            "numbers.remove-trailing-zeros=true",
+            "numbers.lowercase=true",
+        ]
+    );
+}
+
+#[test]
+fn parentheses_settings() {
+    test_settings!(
+        "parentheses",
+        "remove-redundant",
+        ["parentheses.remove-redundant=true"]
+    );
+    test_settings!(
+        "parentheses",
+        "clarify-mixed-logic",
+        [
+            "parentheses.remove-redundant=true",
+            "parentheses.clarify-mixed-logic=true",
+        ]
+    );
+}
+
+#[test]
+fn semicolons_settings() {
+    test_settings!(
+        "semicolons",
+        "removed",
+        [
+            "semicolons.remove-trailing=true",
+            "semicolons.split-statements=true",
+        ]
+    );
+}
+
+/// Normalised strings evaluate to the same value as the original.
+#[test]
+fn strings_round_trip() {
+    let input_data = include_str!("source/strings.typ");
+    let root = typst_syntax::parse(input_data);
+
+    let mut settings = insta::Settings::clone_current();
+    settings.set_prepend_module_to_snapshot(false);
+    settings.set_snapshot_path(Path::new("snapshots"));
+
+    for unicode in ["keep", "literal", "escaped"] {
+        let mut style = Styles::Default.settings();
+        style
+            .overwrite_values(&[
+                "strings.normalize-escapes=true".into(),
+                format!("strings.unicode={unicode}"),
+            ])
+            .unwrap();
+        let mut output = Cursor::new(Vec::new());
+        format_node(&root, &style, &mut output);
+        let output = output.into_inner();
+
+        let formatted = typst_syntax::parse(std::str::from_utf8(&output).unwrap());
+        assert_eq!(string_values(&formatted), string_values(&root));
+
+        settings.set_snapshot_suffix(unicode);
+        settings.bind(|| {
+            insta::assert_binary_snapshot!("strings_normalized.typ", output);
+        })
+    }
+}
+
+/// Line breaks in strings are part of the value and keep their line ending.
+#[test]
+fn line_endings_keep_strings() {
+    let root = typst_syntax::parse("#let a = \"b\nc\"\r\n#let d = \"e\r\nf\"\r\n");
+    for (line_ending, expected) in [
+        ("lf", "#let a = \"b\nc\"\n#let d = \"e\r\nf\"\n"),
+        ("crlf", "#let a = \"b\nc\"\r\n#let d = \"e\r\nf\"\r\n"),
+    ] {
+        let mut style = Styles::Default.settings();
+        style
+            .overwrite_values(&[format!("line-ending={line_ending}")])
+            .unwrap();
+        let mut output = Cursor::new(Vec::new());
+        format_node(&root, &style, &mut output);
+        let output = String::from_utf8(output.into_inner()).unwrap();
+
+        assert_eq!(output, expected);
+        assert_eq!(
+            string_values(&typst_syntax::parse(&output)),
+            string_values(&root)
+        );
+    }
+}
+
+fn string_values(node: &SyntaxNode) -> Vec<String> {
+    match node.cast::<ast::Str>() {
+        Some(value) => vec![value.get().into()],
+        None => node.children().flat_map(string_values).collect(),
+    }
+}
+
 #[test]
 fn all_source_files_used() {
     let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests");
@@ -73,7 +262,7 @@ fn all_source_files_used() {
             if file_type.is_dir() {
                 remaining.push((in_path.join(file_name), out_name));
             } else if file_type.is_file() {
-                for style in ["default", "otbs"] {
+                for style in Styles::value_variants() {
                     let path = out_path.join(format!("{}@{}.snap", out_name, style));
                     assert!(path.exists(), "Snapshot for {} missing", path.display());
                 }
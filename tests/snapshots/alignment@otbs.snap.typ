@@ -0,0 +1,20 @@
+#let a = 1
+#let bcd = 2
+
+#let e = 3
+#{
+	let f = 4
+	// group
+	let gh = 5
+	let i = 6
+} #let j = (
+	k: 1,
+	lmn: 2,
+
+	o: 3,
+)
+#figure(
+	caption: [Caption],
+	placement: top,
+	image("a.png"),
+)
@@ -0,0 +1,26 @@
+#let titles = (query(heading)
+  .filter(h => h.level == 1 and h.outlined)
+  .map(h => h.body)
+  .join([, ], last: [ and ]))
+
+#let short = (1, 2, 3).map(x => x * 2).sum()
+
+#{
+  let chapters = query(heading.where(level: 1))
+    .filter(h => h.outlined)
+    .map(h => (h.body, h.location().page()))
+    .dedup()
+  let count = chapters.len()
+  let text = "a,b".split(",").map(str.trim)
+  let page = here().page()
+}
+
+#figure(caption: [Figures], query(figure.where(kind: image))
+  .filter(f => f.has("caption"))
+  .map(f => f.caption.body)
+  .join(linebreak()))
+
+The #(query(figure)
+  .filter(f => f.kind == image and f.has("caption"))
+  .map(f => f.caption.body)
+  .join[, ]) are images.
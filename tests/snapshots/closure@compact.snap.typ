@@ -0,0 +1,22 @@
+#let double = x => x * 2
+#let first = (x) => x
+#let pair = (a, b) => (a, b)
+#let ignore = _ => none
+#let add(x, y) = x + y
+#let twice(x) = 2 * x
+
+#show heading: it => {
+  set text(red)
+  it
+}
+
+#{
+  let names = items.map(item => {
+    let name = item.name
+    upper(name)
+  })
+  let total = items.fold(0, (sum, item) => {
+    sum + item.count
+  })
+  let short = items.map((item) => item.name)
+}
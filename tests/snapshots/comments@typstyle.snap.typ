@@ -0,0 +1,8 @@
+//test
+#import "headings.typ": *; //test
+//test
+
+#test(
+  "some",
+  // "args",
+)
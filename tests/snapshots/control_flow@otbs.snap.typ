@@ -0,0 +1,25 @@
+#if x > 1 [big] else [small]
+
+#if condition {
+	[yes]
+} else if other {
+	[maybe]
+} else {
+	[no]
+}
+
+#{
+	if a { b } else if c { d } else { e }
+	if x > 1 [
+		big
+	] else [small]
+	while i < 10 { i += 1 }
+	for (key, value) in dict.pairs() [#key: #value]
+	for x in (1, 2, 3) {
+		sum += x
+	}
+}
+
+#for item in items [
+	- #item
+]
@@ -0,0 +1,12 @@
+= Top
+== Sub
+#lorem(1)
+== Sub 2
+=== Subsub 3
+#lorem(1)
+== Sub 3
+#lorem(1)
+
+== Sub 4 <label>
+=== Subsub 4 <label_2>
+#lorem(1)
@@ -0,0 +1,18 @@
+#import "template.typ": project, appendix, project
+#import "@preview/cetz:0.2.2": canvas, draw as cetz-draw, plot
+#import "utils.typ": todo
+#import "@preview/fletcher:0.4.5" as fletcher: (
+  diagram,
+  node,
+  edge,
+  shapes,
+  hide,
+  label-position,
+  cetz,
+)
+
+#import "chapters/intro.typ": *
+
+#{
+  import "lib.typ": zeta, alpha as first-letter, beta
+}
@@ -0,0 +1,18 @@
+#import "@preview/cetz:0.2.2": canvas, draw as cetz-draw, plot
+#import "@preview/fletcher:0.4.5" as fletcher: (
+  cetz,
+  diagram,
+  edge,
+  hide,
+  label-position,
+  node,
+  shapes,
+)
+#import "template.typ": appendix, project
+#import "utils.typ": todo
+
+#import "chapters/intro.typ": *
+
+#{
+  import "lib.typ": alpha as first-letter, beta, zeta
+}
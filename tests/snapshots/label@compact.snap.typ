@@ -0,0 +1,7 @@
+#figure([]) <a>
+
+#figure([]) <b>
+
+#ref(<a>)
+
+#ref(<a>)
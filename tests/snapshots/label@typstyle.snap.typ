@@ -0,0 +1,9 @@
+#figure([]) <a>
+
+#figure([]) <b>
+
+#ref(<a>)
+
+#ref(
+  <a>,
+)
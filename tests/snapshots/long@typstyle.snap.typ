@@ -0,0 +1,27 @@
+= Top Level
+
+Very long long long long long long long long long long long long long long long long long long long long long text
+
+Very long long long long long long long long long long long long long long long long long long long long long text
+Something short
+
+== In Block
+
+#test(
+  [Very long long long long long long long long long long long long long long long long long long long long long text],
+)
+
+#test(
+  test(
+    test(
+      test(
+        [Very long long long long long long long long long long long long long long long long long long long long long text],
+      ),
+    ),
+  ),
+)
+
+== Exceptions
+
+*Very long long long long long long long long long long long long long long long long long long long long long text*
+_Very long long long long long long long long long long long long long long long long long long long long long text_
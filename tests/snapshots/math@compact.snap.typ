@@ -0,0 +1,14 @@
+$0$
+
+$0$
+
+$0$
+
+$ 0 $
+
+$ 100 & 1   \
+      & 100 \ $
+
+$
+  1000 &  &  &  \
+       &  &  &  \ $
@@ -0,0 +1,17 @@
+$0$
+
+$0$
+
+$0$
+
+$ 0 $
+
+$
+  100 & 1   \
+      & 100 \
+$
+
+$
+  1000 &  &  &  \
+       &  &  &  \
+$
@@ -0,0 +1,4 @@
+#let a = (.5em, 0.50em, 1.0pt, 1.00, 5., .0, 1.50E3, 0xFF, 0b10, 12%, 7)
+#let b = 2.50 * 1E2 + 0.0
+
+$x = 1.50 + .5$
@@ -0,0 +1,4 @@
+#let a = (0.5em, 0.5em, 1pt, 1.0, 5.0, 0.0, 1.5e3, 0xff, 0b10, 12%, 7)
+#let b = 2.5 * 1e2 + 0.0
+
+$x = 1.50 + .5$
@@ -0,0 +1,10 @@
+#let a = ((1)) + (b) * (c + d) - (e - f)
+#let g = ((h): (1), i: (j.k)(l), m: (x,), n: (:))
+#let o = (p or q and r) or (s and t)
+#(u)v
+
+#{
+  let w = not (x and y) or (not z)
+  let v = (a.b) * (c * d) / (e / f)
+  ((f.g))(h)
+}
@@ -0,0 +1,5 @@
+#{
+	let a = 1; let b = 2;
+	a; // c
+	b;
+} #{ let c = 3; c; } #let d = 4;
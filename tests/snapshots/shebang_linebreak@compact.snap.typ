@@ -0,0 +1,2 @@
+#!linebreak
+asdf
@@ -0,0 +1,12 @@
+#{
+  h(1pt)
+  h(1pt)
+  h(
+    1pt,
+  )
+  h(
+    1pt,
+  )
+  _ = (1)
+  _ = (1,)
+}
@@ -0,0 +1,6 @@
+#let a = "\u{0041}\u{42}\u{1F600} \u{0022}\u{5C}\u{A}\u{9}"
+#let b = "caf\u{E9} naïve \u{200B} \u{2192}"
+#let c = "\a \u{41x} \u {} \u{D800} \\ \" \n"
+#let d = ("ü", "\u{FC}", "\u{0000FC}", "\u{301}")
+
+Escapes \u{0023} \u{00E9}\u{2192} \# \é and $x \u{3B1} \u{2192} y$.
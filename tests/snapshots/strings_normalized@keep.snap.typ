@@ -0,0 +1,6 @@
+#let a = "AB\u{1f600} \"\\\n\t"
+#let b = "caf\u{e9} naïve \u{200b} \u{2192}"
+#let c = "\a \u{41x} \u {} \u{D800} \\ \" \n"
+#let d = ("ü", "\u{fc}", "\u{fc}", "\u{301}")
+
+Escapes \# \u{e9}\u{2192} \# \é and $x \u{3b1} \u{2192} y$.
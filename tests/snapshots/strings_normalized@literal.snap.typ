@@ -0,0 +1,6 @@
+#let a = "AB😀 \"\\\n\t"
+#let b = "café naïve \u{200b} →"
+#let c = "\a \u{41x} \u {} \u{D800} \\ \" \n"
+#let d = ("ü", "ü", "ü", "\u{301}")
+
+Escapes \# \u{e9}→ \# \é and $x \u{3b1} → y$.
@@ -0,0 +1,24 @@
+/ Test:
+  Some long, irrelevant body.
+  Spanning multiple lines.
+
+= Seperator
+
+/ A: A
+/ B: B
+/ C: C
+/ D:
+  D
+
+= Seperator
+
+/ A: A
+/ A: A
+
+/ A: A
+
+= Seperator
+
+/ A: #test(
+    [],
+  )
@@ -0,0 +1,17 @@
+#let visible = heading.level == 1 and heading.outlined and heading.numbering != none and not heading.body in hidden-titles
+
+#show heading: it => it.level <= 2 and it.outlined and it.numbering != none and it.supplement == [Chapter]
+
+#let total = first-chapter-pages + second-chapter-pages + third-chapter-pages * 2 - appendix-pages - index-pages
+
+#let body = [
+  First
+] + [
+  Second
+]
+
+#{
+  let long = (chapter-number > 0 or appendix-number > 0) and not title-text in excluded-titles-list-for-outline
+  let short = a + b
+  assert(long-condition-name and another-long-condition-name or yet-another-fairly-long-condition-name)
+}